@@ -0,0 +1,77 @@
+#!/usr/bin/env bash
+# Manual smoke check, not run by `cargo test`: sends the hand-written CalDAV
+# client requests of this directory to a running server, in order, and
+# checks every answer:
+#
+#   fixtures/caldav/replay.sh http://127.0.0.1:8080 <username> <caldav-token>
+#
+# The token is created with `POST /api/v1/caldav/token`.
+#
+# `NN-name.req` holds the request line (`METHOD path`), the headers, an
+# empty line and the body. `NN-name.expect` holds the expected status, then
+# one text per line the response (headers and body) must contain, or must
+# not contain when it starts with `!`. `{username}` is replaced by the
+# account used and `{run}` by a value unique to each run.
+# Requests are sent with Basic authentication unless they carry their own
+# `Authorization` header.
+set -u
+
+dir=$(cd "$(dirname "$0")" && pwd)
+base=${1:?usage: replay.sh <base-url> <username> <caldav-token>}
+username=${2:?usage: replay.sh <base-url> <username> <caldav-token>}
+token=${3:?usage: replay.sh <base-url> <username> <caldav-token>}
+run=$(date +%s)
+failed=0
+
+fill() {
+    sed -e "s/{username}/$username/g" -e "s/{run}/$run/g" "$1"
+}
+
+for req in "$dir"/*.req; do
+    name=$(basename "$req" .req)
+    content=$(fill "$req")
+    request_line=$(printf '%s\n' "$content" | head -n 1)
+    method=${request_line%% *}
+    path=${request_line#* }
+    args=(-s -i -X "$method")
+    auth=(-u "$username:$token")
+    while IFS= read -r line; do
+        [ -z "$line" ] && break
+        case "$line" in
+            Authorization:*) auth=() ;;
+        esac
+        args+=(-H "$line")
+    done < <(printf '%s\n' "$content" | tail -n +2)
+    body=$(printf '%s\n' "$content" | tail -n +2 | sed '1,/^$/d')
+    if [ -n "$body" ]; then
+        args+=(--data-binary "$body")
+    fi
+    response=$(curl "${args[@]}" "${auth[@]}" "$base$path")
+    status=$(printf '%s\n' "$response" | head -n 1 | cut -d ' ' -f 2)
+
+    errors=()
+    expected_status=$(fill "$dir/$name.expect" | head -n 1)
+    if [ "$status" != "$expected_status" ]; then
+        errors+=("status $status, expected $expected_status")
+    fi
+    while IFS= read -r pattern; do
+        [ -z "$pattern" ] && continue
+        if [ "${pattern:0:1}" = "!" ]; then
+            if printf '%s' "$response" | grep -qF -- "${pattern:1}"; then
+                errors+=("unexpected: ${pattern:1}")
+            fi
+        elif ! printf '%s' "$response" | grep -qF -- "$pattern"; then
+            errors+=("missing: $pattern")
+        fi
+    done < <(fill "$dir/$name.expect" | tail -n +2)
+
+    if [ ${#errors[@]} -eq 0 ]; then
+        echo "ok   $name"
+    else
+        failed=$((failed + 1))
+        echo "FAIL $name"
+        printf '     %s\n' "${errors[@]}"
+    fi
+done
+
+[ $failed -eq 0 ] || { echo "$failed fixture(s) failed"; exit 1; }
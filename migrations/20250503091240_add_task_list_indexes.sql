@@ -0,0 +1,6 @@
+-- Add migration script here
+CREATE INDEX IF NOT EXISTS tasks_user_id_created_at_idx
+ON tasks (user_id, created_at, id);
+
+CREATE INDEX IF NOT EXISTS tasks_user_id_updated_at_idx
+ON tasks (user_id, updated_at, id);
@@ -0,0 +1,13 @@
+-- Add migration script here
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS timezone VARCHAR(64) NOT NULL DEFAULT 'UTC';
+
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS start_at TIMESTAMPTZ NULL,
+ADD COLUMN IF NOT EXISTS due_at TIMESTAMPTZ NULL,
+ADD CONSTRAINT tasks_start_before_due
+    CHECK (start_at IS NULL OR due_at IS NULL OR start_at <= due_at);
+
+CREATE INDEX IF NOT EXISTS tasks_user_id_due_at_idx
+ON tasks (user_id, due_at)
+WHERE due_at IS NOT NULL;
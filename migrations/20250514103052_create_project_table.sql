@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS projects (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (id, user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS project_id INT NULL,
+ADD CONSTRAINT tasks_project_fk
+    FOREIGN KEY (project_id, user_id) REFERENCES projects(id, user_id);
+
+CREATE INDEX IF NOT EXISTS tasks_project_id_idx
+ON tasks (project_id)
+WHERE project_id IS NOT NULL;
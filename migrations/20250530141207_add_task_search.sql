@@ -0,0 +1,10 @@
+-- Add migration script here
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS search TSVECTOR
+GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', COALESCE(title, '')), 'A') ||
+    setweight(to_tsvector('simple', COALESCE(body, '')), 'B')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS tasks_search_idx
+ON tasks USING GIN (search);
@@ -0,0 +1,24 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS task_series (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL,
+    frequency VARCHAR(10) NOT NULL CHECK (frequency IN ('DAILY', 'WEEKLY', 'MONTHLY', 'AFTER_DONE')),
+    every INT NOT NULL DEFAULT 1 CHECK (every >= 1),
+    weekdays INT[] NULL,
+    month_day INT NULL CHECK (month_day BETWEEN 1 AND 31),
+    title VARCHAR(255) NOT NULL,
+    body TEXT NULL,
+    priority VARCHAR(6) NOT NULL DEFAULT 'MEDIUM',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS series_id INT NULL,
+ADD CONSTRAINT tasks_series_fk
+    FOREIGN KEY (series_id) REFERENCES task_series(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS tasks_series_id_idx
+ON tasks (series_id)
+WHERE series_id IS NOT NULL;
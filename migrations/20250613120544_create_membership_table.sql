@@ -0,0 +1,96 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS memberships (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL,
+    task_id INT NULL,
+    project_id INT NULL,
+    role VARCHAR(6) NOT NULL CHECK (role IN ('VIEWER', 'EDITOR', 'OWNER')),
+    state VARCHAR(8) NOT NULL CHECK (state IN ('PENDING', 'ACCEPTED')) DEFAULT 'PENDING',
+    invited_by INT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((task_id IS NULL) <> (project_id IS NULL)),
+    UNIQUE (user_id, task_id),
+    UNIQUE (user_id, project_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS memberships_task_id_idx
+ON memberships (task_id)
+WHERE task_id IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS memberships_project_id_idx
+ON memberships (project_id)
+WHERE project_id IS NOT NULL;
+
+-- The creator of a task or project is its first owner.
+CREATE OR REPLACE FUNCTION add_owner_membership()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_TABLE_NAME = 'tasks' THEN
+        INSERT INTO memberships (user_id, task_id, role, state)
+        VALUES (NEW.user_id, NEW.id, 'OWNER', 'ACCEPTED');
+    ELSE
+        INSERT INTO memberships (user_id, project_id, role, state)
+        VALUES (NEW.user_id, NEW.id, 'OWNER', 'ACCEPTED');
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER add_task_owner
+AFTER INSERT ON tasks
+FOR EACH ROW
+EXECUTE FUNCTION add_owner_membership();
+
+CREATE TRIGGER add_project_owner
+AFTER INSERT ON projects
+FOR EACH ROW
+EXECUTE FUNCTION add_owner_membership();
+
+INSERT INTO memberships (user_id, task_id, role, state)
+SELECT user_id, id, 'OWNER', 'ACCEPTED' FROM tasks
+ON CONFLICT DO NOTHING;
+
+INSERT INTO memberships (user_id, project_id, role, state)
+SELECT user_id, id, 'OWNER', 'ACCEPTED' FROM projects
+ON CONFLICT DO NOTHING;
+
+-- Accepted access per user, either to the task itself or through its
+-- project. `rank` orders the roles: 1 viewer, 2 editor, 3 owner.
+CREATE OR REPLACE VIEW task_access AS
+SELECT task_id, user_id, MAX(rank) AS rank
+FROM (
+    SELECT
+        memberships.task_id,
+        memberships.user_id,
+        CASE memberships.role WHEN 'OWNER' THEN 3 WHEN 'EDITOR' THEN 2 ELSE 1 END AS rank
+    FROM memberships
+    WHERE
+        memberships.task_id IS NOT NULL AND
+        memberships.state = 'ACCEPTED'
+    UNION ALL
+    SELECT
+        tasks.id,
+        memberships.user_id,
+        CASE memberships.role WHEN 'OWNER' THEN 3 WHEN 'EDITOR' THEN 2 ELSE 1 END AS rank
+    FROM memberships
+    JOIN tasks ON tasks.project_id = memberships.project_id
+    WHERE
+        memberships.project_id IS NOT NULL AND
+        memberships.state = 'ACCEPTED'
+) access
+GROUP BY task_id, user_id;
+
+CREATE OR REPLACE VIEW project_access AS
+SELECT
+    project_id,
+    user_id,
+    CASE role WHEN 'OWNER' THEN 3 WHEN 'EDITOR' THEN 2 ELSE 1 END AS rank
+FROM memberships
+WHERE
+    project_id IS NOT NULL AND
+    state = 'ACCEPTED';
@@ -0,0 +1,64 @@
+-- Add migration script here
+-- No foreign key on `task_id`: the history of a task outlives it.
+CREATE TABLE IF NOT EXISTS task_history (
+    id SERIAL PRIMARY KEY,
+    task_id INT NOT NULL,
+    user_id INT,
+    action VARCHAR(6) NOT NULL CHECK (action IN ('CREATE', 'UPDATE', 'DELETE')),
+    changes JSONB NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS task_history_task_id_idx
+ON task_history (task_id, id);
+
+-- Records the columns a statement actually changed as
+-- `{"column": {"old": .., "new": ..}}`. The acting user is read from the
+-- `todolistify.user_id` setting, set per transaction by the services.
+CREATE OR REPLACE FUNCTION record_task_history()
+RETURNS TRIGGER AS $$
+DECLARE
+    old_row JSONB := CASE WHEN TG_OP = 'INSERT' THEN '{}' ELSE to_jsonb(OLD) END;
+    new_row JSONB := CASE WHEN TG_OP = 'DELETE' THEN '{}' ELSE to_jsonb(NEW) END;
+    task_changes JSONB;
+BEGIN
+    SELECT jsonb_object_agg(
+        key,
+        jsonb_build_object('old', old_row -> key, 'new', new_row -> key)
+    )
+    INTO task_changes
+    FROM (
+        SELECT jsonb_object_keys(old_row || new_row) AS key
+    ) keys
+    WHERE
+        key NOT IN ('id', 'search', 'updated_at', 'created_at') AND
+        (old_row -> key) IS DISTINCT FROM (new_row -> key) AND
+        NOT (
+            COALESCE(old_row -> key, 'null') = 'null' AND
+            COALESCE(new_row -> key, 'null') = 'null'
+        );
+
+    IF task_changes IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO task_history (task_id, user_id, action, changes)
+    VALUES (
+        COALESCE(NEW.id, OLD.id),
+        NULLIF(current_setting('todolistify.user_id', true), '')::INT,
+        CASE TG_OP
+            WHEN 'INSERT' THEN 'CREATE'
+            WHEN 'UPDATE' THEN 'UPDATE'
+            ELSE 'DELETE'
+        END,
+        task_changes
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_task_history
+AFTER INSERT OR UPDATE OR DELETE ON tasks
+FOR EACH ROW
+EXECUTE FUNCTION record_task_history();
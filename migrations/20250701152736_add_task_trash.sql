@@ -0,0 +1,85 @@
+-- Add migration script here
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS tasks_deleted_at_idx
+ON tasks (deleted_at)
+WHERE deleted_at IS NOT NULL;
+
+-- `task_access` keeps its meaning for live tasks, so trashed tasks vanish
+-- from every listing and endpoint. `trash_access` is the same for the trash.
+ALTER VIEW task_access RENAME TO any_task_access;
+
+CREATE OR REPLACE VIEW task_access AS
+SELECT any_task_access.*
+FROM any_task_access
+JOIN tasks ON tasks.id = any_task_access.task_id
+WHERE tasks.deleted_at IS NULL;
+
+CREATE OR REPLACE VIEW trash_access AS
+SELECT any_task_access.*
+FROM any_task_access
+JOIN tasks ON tasks.id = any_task_access.task_id
+WHERE tasks.deleted_at IS NOT NULL;
+
+-- Moving to and out of the trash are recorded as their own actions.
+ALTER TABLE task_history
+DROP CONSTRAINT IF EXISTS task_history_action_check;
+
+ALTER TABLE task_history
+ALTER COLUMN action TYPE VARCHAR(7);
+
+ALTER TABLE task_history
+ADD CONSTRAINT task_history_action_check
+CHECK (action IN ('CREATE', 'UPDATE', 'TRASH', 'RESTORE', 'DELETE'));
+
+CREATE OR REPLACE FUNCTION record_task_history()
+RETURNS TRIGGER AS $$
+DECLARE
+    old_row JSONB := CASE WHEN TG_OP = 'INSERT' THEN '{}' ELSE to_jsonb(OLD) END;
+    new_row JSONB := CASE WHEN TG_OP = 'DELETE' THEN '{}' ELSE to_jsonb(NEW) END;
+    task_action VARCHAR(7) := CASE TG_OP
+        WHEN 'INSERT' THEN 'CREATE'
+        WHEN 'DELETE' THEN 'DELETE'
+        ELSE 'UPDATE'
+    END;
+    task_changes JSONB;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        IF OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+            task_action := 'TRASH';
+        ELSIF OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL THEN
+            task_action := 'RESTORE';
+        END IF;
+    END IF;
+
+    SELECT jsonb_object_agg(
+        key,
+        jsonb_build_object('old', old_row -> key, 'new', new_row -> key)
+    )
+    INTO task_changes
+    FROM (
+        SELECT jsonb_object_keys(old_row || new_row) AS key
+    ) keys
+    WHERE
+        key NOT IN ('id', 'search', 'updated_at', 'created_at') AND
+        (old_row -> key) IS DISTINCT FROM (new_row -> key) AND
+        NOT (
+            COALESCE(old_row -> key, 'null') = 'null' AND
+            COALESCE(new_row -> key, 'null') = 'null'
+        );
+
+    IF task_changes IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO task_history (task_id, user_id, action, changes)
+    VALUES (
+        COALESCE(NEW.id, OLD.id),
+        NULLIF(current_setting('todolistify.user_id', true), '')::INT,
+        task_action,
+        task_changes
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
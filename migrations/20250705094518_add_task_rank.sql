@@ -0,0 +1,38 @@
+-- Add migration script here
+-- Fractional rank for manual ordering: a task moved between two neighbours
+-- takes the midpoint of their ranks, so no other row is renumbered.
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS rank NUMERIC;
+
+UPDATE tasks
+SET rank = ranked.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY id) AS position
+    FROM tasks
+) ranked
+WHERE tasks.id = ranked.id;
+
+ALTER TABLE tasks
+ALTER COLUMN rank SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS tasks_user_id_rank_idx
+ON tasks (user_id, rank, id);
+
+-- New tasks go to the end of their owner's list.
+CREATE OR REPLACE FUNCTION set_task_rank()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.rank IS NULL THEN
+        NEW.rank := COALESCE((
+            SELECT floor(MAX(rank)) FROM tasks
+            WHERE user_id = NEW.user_id
+        ), 0) + 1;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_task_rank
+BEFORE INSERT ON tasks
+FOR EACH ROW
+EXECUTE FUNCTION set_task_rank();
@@ -0,0 +1,69 @@
+-- Add migration script here
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS version INT NOT NULL DEFAULT 1;
+
+-- Every write to the row moves the version on, whatever service made it.
+CREATE OR REPLACE FUNCTION bump_task_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bump_task_version
+BEFORE UPDATE ON tasks
+FOR EACH ROW
+EXECUTE FUNCTION bump_task_version();
+
+-- The version changes with every update, so it is left out of the history.
+CREATE OR REPLACE FUNCTION record_task_history()
+RETURNS TRIGGER AS $$
+DECLARE
+    old_row JSONB := CASE WHEN TG_OP = 'INSERT' THEN '{}' ELSE to_jsonb(OLD) END;
+    new_row JSONB := CASE WHEN TG_OP = 'DELETE' THEN '{}' ELSE to_jsonb(NEW) END;
+    task_action VARCHAR(7) := CASE TG_OP
+        WHEN 'INSERT' THEN 'CREATE'
+        WHEN 'DELETE' THEN 'DELETE'
+        ELSE 'UPDATE'
+    END;
+    task_changes JSONB;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        IF OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+            task_action := 'TRASH';
+        ELSIF OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL THEN
+            task_action := 'RESTORE';
+        END IF;
+    END IF;
+
+    SELECT jsonb_object_agg(
+        key,
+        jsonb_build_object('old', old_row -> key, 'new', new_row -> key)
+    )
+    INTO task_changes
+    FROM (
+        SELECT jsonb_object_keys(old_row || new_row) AS key
+    ) keys
+    WHERE
+        key NOT IN ('id', 'search', 'updated_at', 'created_at', 'version') AND
+        (old_row -> key) IS DISTINCT FROM (new_row -> key) AND
+        NOT (
+            COALESCE(old_row -> key, 'null') = 'null' AND
+            COALESCE(new_row -> key, 'null') = 'null'
+        );
+
+    IF task_changes IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO task_history (task_id, user_id, action, changes)
+    VALUES (
+        COALESCE(NEW.id, OLD.id),
+        NULLIF(current_setting('todolistify.user_id', true), '')::INT,
+        task_action,
+        task_changes
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
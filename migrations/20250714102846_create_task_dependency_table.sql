@@ -0,0 +1,14 @@
+-- Add migration script here
+-- `task_id` can NOT start until `blocked_by_id` is done.
+CREATE TABLE IF NOT EXISTS task_dependencies (
+    task_id INT NOT NULL,
+    blocked_by_id INT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (task_id, blocked_by_id),
+    CHECK (task_id <> blocked_by_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (blocked_by_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS task_dependencies_blocked_by_id_idx
+ON task_dependencies (blocked_by_id);
@@ -0,0 +1,9 @@
+-- Add migration script here
+-- Only the SHA-256 of the token is kept; the token itself is shown once.
+CREATE TABLE IF NOT EXISTS feed_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INT UNIQUE NOT NULL,
+    token_hash CHAR(64) UNIQUE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,10 @@
+-- Add migration script here
+-- Name and UID chosen by a CalDAV client for a task it created. Other tasks
+-- are served as `task-<id>.ics` with the UID `task-<id>@todolistify`.
+CREATE TABLE IF NOT EXISTS caldav_objects (
+    task_id INT PRIMARY KEY,
+    name VARCHAR(255) UNIQUE NOT NULL,
+    uid VARCHAR(255) UNIQUE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
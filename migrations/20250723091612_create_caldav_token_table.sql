@@ -0,0 +1,10 @@
+-- Add migration script here
+-- CalDAV clients authenticate with this token instead of the account
+-- password. As for feed tokens, only its SHA-256 is kept.
+CREATE TABLE IF NOT EXISTS caldav_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INT UNIQUE NOT NULL,
+    token_hash CHAR(64) UNIQUE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
\ No newline at end of file
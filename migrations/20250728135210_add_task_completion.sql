@@ -0,0 +1,44 @@
+-- Add migration script here
+ALTER TABLE tasks
+ADD COLUMN IF NOT EXISTS completed_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS tasks_completed_at_idx
+ON tasks (completed_at)
+WHERE completed_at IS NOT NULL;
+
+-- Set whenever a task reaches `DONE` and cleared when it leaves it, whatever
+-- service wrote the state.
+CREATE OR REPLACE FUNCTION set_task_completed_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.state <> 'DONE' THEN
+        NEW.completed_at := NULL;
+    ELSIF TG_OP = 'INSERT' OR OLD.state <> 'DONE' THEN
+        NEW.completed_at := CURRENT_TIMESTAMP;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_task_completed_at
+BEFORE INSERT OR UPDATE OF state ON tasks
+FOR EACH ROW
+EXECUTE FUNCTION set_task_completed_at();
+
+-- Tasks already done were completed when their history last moved them to
+-- `DONE`, or created done. The backfill is neither a user edit nor a new
+-- version of the task.
+ALTER TABLE tasks DISABLE TRIGGER record_task_history;
+ALTER TABLE tasks DISABLE TRIGGER bump_task_version;
+
+UPDATE tasks
+SET completed_at = COALESCE((
+    SELECT MAX(created_at) FROM task_history
+    WHERE
+        task_id = tasks.id AND
+        changes -> 'state' ->> 'new' = 'DONE'
+), updated_at, created_at)
+WHERE state = 'DONE';
+
+ALTER TABLE tasks ENABLE TRIGGER record_task_history;
+ALTER TABLE tasks ENABLE TRIGGER bump_task_version;
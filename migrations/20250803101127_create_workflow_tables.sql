@@ -0,0 +1,173 @@
+-- Add migration script here
+-- Ordered task states. Every user has one default workflow, used by the
+-- inbox and by projects without a workflow of their own.
+CREATE TABLE IF NOT EXISTS workflows (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (id, user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS workflows_default_idx
+ON workflows (user_id)
+WHERE is_default;
+
+-- The first state is where new tasks start. Tasks in a `closed` state are
+-- finished: they are never overdue and do not block other tasks.
+CREATE TABLE IF NOT EXISTS workflow_states (
+    id SERIAL PRIMARY KEY,
+    workflow_id INT NOT NULL,
+    name VARCHAR(32) NOT NULL,
+    position INT NOT NULL,
+    closed BOOLEAN NOT NULL DEFAULT FALSE,
+    CONSTRAINT workflow_states_name_key
+        UNIQUE (workflow_id, name) DEFERRABLE INITIALLY DEFERRED,
+    CONSTRAINT workflow_states_position_key
+        UNIQUE (workflow_id, position) DEFERRABLE INITIALLY DEFERRED,
+    FOREIGN KEY (workflow_id) REFERENCES workflows(id) ON DELETE CASCADE
+);
+
+CREATE OR REPLACE FUNCTION create_default_workflow(owner_id INT)
+RETURNS INT AS $$
+DECLARE
+    new_workflow_id INT;
+BEGIN
+    INSERT INTO workflows (user_id, name, is_default)
+    VALUES (owner_id, 'Default', TRUE)
+    RETURNING id INTO new_workflow_id;
+
+    INSERT INTO workflow_states (workflow_id, name, position, closed)
+    VALUES
+        (new_workflow_id, 'TO_DO', 0, FALSE),
+        (new_workflow_id, 'IN_PROGRESS', 1, FALSE),
+        (new_workflow_id, 'DONE', 2, TRUE);
+    RETURN new_workflow_id;
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT create_default_workflow(id) FROM users;
+
+CREATE OR REPLACE FUNCTION create_user_workflow()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM create_default_workflow(NEW.id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER create_user_workflow
+AFTER INSERT ON users
+FOR EACH ROW
+EXECUTE FUNCTION create_user_workflow();
+
+ALTER TABLE projects
+ADD COLUMN IF NOT EXISTS workflow_id INT NULL,
+ADD CONSTRAINT projects_workflow_fk
+    FOREIGN KEY (workflow_id, user_id) REFERENCES workflows(id, user_id);
+
+-- The workflow of a task: its project's, else the default workflow of the
+-- project owner, who also owns the task.
+CREATE OR REPLACE FUNCTION task_workflow_id(owner_id INT, task_project_id INT)
+RETURNS INT AS $$
+    SELECT COALESCE(projects.workflow_id, workflows.id)
+    FROM (SELECT 1) AS one
+    LEFT JOIN projects ON projects.id = task_project_id
+    LEFT JOIN workflows ON
+        workflows.user_id = COALESCE(projects.user_id, owner_id) AND
+        workflows.is_default;
+$$ LANGUAGE sql STABLE;
+
+-- Replaced by `resolve_task_state` below, which knows which states close.
+DROP TRIGGER IF EXISTS set_task_completed_at ON tasks;
+DROP FUNCTION IF EXISTS set_task_completed_at();
+
+ALTER TABLE tasks
+DROP CONSTRAINT IF EXISTS tasks_state_check;
+
+ALTER TABLE tasks
+ALTER COLUMN state TYPE VARCHAR(32),
+ALTER COLUMN state DROP DEFAULT,
+ADD COLUMN IF NOT EXISTS state_id INT NULL,
+ADD COLUMN IF NOT EXISTS closed BOOLEAN NOT NULL DEFAULT FALSE,
+ADD CONSTRAINT tasks_state_fk
+    FOREIGN KEY (state_id) REFERENCES workflow_states(id);
+
+CREATE INDEX IF NOT EXISTS tasks_state_id_idx
+ON tasks (state_id);
+
+-- Existing tasks land on the same state of their owner's default workflow.
+-- The backfill is neither a user edit nor a new version of the task.
+ALTER TABLE tasks DISABLE TRIGGER record_task_history;
+ALTER TABLE tasks DISABLE TRIGGER bump_task_version;
+
+UPDATE tasks
+SET
+    state    = COALESCE(state, 'TO_DO'),
+    state_id = workflow_states.id,
+    closed   = workflow_states.closed
+FROM workflow_states
+WHERE
+    workflow_states.workflow_id = task_workflow_id(tasks.user_id, tasks.project_id) AND
+    workflow_states.name        = COALESCE(tasks.state, 'TO_DO');
+
+ALTER TABLE tasks ENABLE TRIGGER record_task_history;
+ALTER TABLE tasks ENABLE TRIGGER bump_task_version;
+
+ALTER TABLE tasks
+ALTER COLUMN state SET NOT NULL,
+ALTER COLUMN state_id SET NOT NULL;
+
+-- Checks the state against the workflow of the task and keeps `state_id`,
+-- `closed` and `completed_at` in step with it. A task without a state gets
+-- the first one. A task whose workflow changed under it, by a move or a
+-- project switching workflows, keeps its state when the new workflow has
+-- one of that name, else takes the first state that is as closed as before.
+CREATE OR REPLACE FUNCTION resolve_task_state()
+RETURNS TRIGGER AS $$
+DECLARE
+    flow_id INT := task_workflow_id(NEW.user_id, NEW.project_id);
+    picked workflow_states%ROWTYPE;
+BEGIN
+    IF NEW.state IS NULL THEN
+        SELECT * INTO picked FROM workflow_states
+        WHERE workflow_id = flow_id
+        ORDER BY position
+        LIMIT 1;
+    ELSE
+        SELECT * INTO picked FROM workflow_states
+        WHERE
+            workflow_id = flow_id AND
+            name        = NEW.state;
+        IF picked.id IS NULL AND TG_OP = 'UPDATE' AND NEW.state = OLD.state THEN
+            SELECT * INTO picked FROM workflow_states
+            WHERE workflow_id = flow_id
+            ORDER BY closed <> OLD.closed, position
+            LIMIT 1;
+        END IF;
+    END IF;
+
+    IF picked.id IS NULL THEN
+        RAISE EXCEPTION 'state % is not in the workflow of the task', NEW.state
+        USING ERRCODE = 'check_violation', CONSTRAINT = 'tasks_state_check';
+    END IF;
+
+    IF NOT picked.closed THEN
+        NEW.completed_at := NULL;
+    ELSIF TG_OP = 'INSERT' OR NOT OLD.closed THEN
+        NEW.completed_at := CURRENT_TIMESTAMP;
+    END IF;
+    NEW.state    := picked.name;
+    NEW.state_id := picked.id;
+    NEW.closed   := picked.closed;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER resolve_task_state
+BEFORE INSERT OR UPDATE OF state, project_id ON tasks
+FOR EACH ROW
+EXECUTE FUNCTION resolve_task_state();
@@ -0,0 +1,27 @@
+-- Add migration script here
+-- Time a user spent on a task. A timer is an entry without `ended_at`
+-- yet, and a user has at most one of those running.
+CREATE TABLE IF NOT EXISTS time_entries (
+    id SERIAL PRIMARY KEY,
+    task_id INT NOT NULL,
+    user_id INT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ NULL,
+    note VARCHAR(255) NOT NULL DEFAULT '',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT time_entries_range_check
+        CHECK (ended_at IS NULL OR ended_at >= started_at),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS time_entries_running_idx
+ON time_entries (user_id)
+WHERE ended_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS time_entries_task_id_idx
+ON time_entries (task_id, started_at);
+
+CREATE INDEX IF NOT EXISTS time_entries_user_id_idx
+ON time_entries (user_id, started_at);
@@ -0,0 +1,27 @@
+-- Add migration script here
+-- Saved starting points for tasks. A template is private to its user.
+CREATE TABLE IF NOT EXISTS task_templates (
+    id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL DEFAULT '',
+    priority VARCHAR(6) NOT NULL DEFAULT 'MEDIUM' CHECK (priority IN ('LOW', 'MEDIUM', 'HIGH')),
+    -- Titles of the checklist items, in order.
+    checklist TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, name),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS task_template_tags (
+    template_id INT NOT NULL,
+    tag_id INT NOT NULL,
+    PRIMARY KEY (template_id, tag_id),
+    FOREIGN KEY (template_id) REFERENCES task_templates(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS task_template_tags_tag_id_idx
+ON task_template_tags (tag_id);
@@ -0,0 +1,150 @@
+-- Add migration script here
+-- The version is the task's ETag, so it also moves on when a write elsewhere
+-- changes what a task reads as: its counts, attachments, tags, blocked flag
+-- or recurrence rule. Touching the row lets `bump_task_version` do the bump,
+-- and the history leaves the version out, so no entry is recorded.
+CREATE OR REPLACE FUNCTION touch_tasks()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        UPDATE tasks SET version = version
+        WHERE id IN (SELECT task_id FROM old_rows);
+    ELSE
+        UPDATE tasks SET version = version
+        WHERE id IN (SELECT task_id FROM new_rows);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER touch_comment_task_insert
+AFTER INSERT ON comments
+REFERENCING NEW TABLE AS new_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_comment_task_delete
+AFTER DELETE ON comments
+REFERENCING OLD TABLE AS old_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_checklist_task_insert
+AFTER INSERT ON checklist_items
+REFERENCING NEW TABLE AS new_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_checklist_task_update
+AFTER UPDATE ON checklist_items
+REFERENCING NEW TABLE AS new_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_checklist_task_delete
+AFTER DELETE ON checklist_items
+REFERENCING OLD TABLE AS old_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_attachment_task_insert
+AFTER INSERT ON attachments
+REFERENCING NEW TABLE AS new_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_attachment_task_delete
+AFTER DELETE ON attachments
+REFERENCING OLD TABLE AS old_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_tag_task_insert
+AFTER INSERT ON task_tags
+REFERENCING NEW TABLE AS new_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_tag_task_delete
+AFTER DELETE ON task_tags
+REFERENCING OLD TABLE AS old_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+-- A new or removed blocker changes the `blocked` flag of the task.
+CREATE TRIGGER touch_dependency_task_insert
+AFTER INSERT ON task_dependencies
+REFERENCING NEW TABLE AS new_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+CREATE TRIGGER touch_dependency_task_delete
+AFTER DELETE ON task_dependencies
+REFERENCING OLD TABLE AS old_rows
+FOR EACH STATEMENT
+EXECUTE FUNCTION touch_tasks();
+
+-- So does a blocker being closed, reopened, trashed or restored.
+CREATE OR REPLACE FUNCTION touch_blocked_tasks()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE tasks SET version = version
+    WHERE id IN (
+        SELECT task_id FROM task_dependencies
+        WHERE blocked_by_id = NEW.id
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER touch_blocked_tasks
+AFTER UPDATE ON tasks
+FOR EACH ROW
+WHEN (
+    OLD.closed IS DISTINCT FROM NEW.closed OR
+    OLD.deleted_at IS DISTINCT FROM NEW.deleted_at
+)
+EXECUTE FUNCTION touch_blocked_tasks();
+
+-- Tasks show the name and color of their tags.
+CREATE OR REPLACE FUNCTION touch_tagged_tasks()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE tasks SET version = version
+    WHERE id IN (
+        SELECT task_id FROM task_tags
+        WHERE tag_id = NEW.id
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER touch_tagged_tasks
+AFTER UPDATE ON tags
+FOR EACH ROW
+WHEN (
+    OLD.name IS DISTINCT FROM NEW.name OR
+    OLD.color IS DISTINCT FROM NEW.color
+)
+EXECUTE FUNCTION touch_tagged_tasks();
+
+-- And the rule of their series.
+CREATE OR REPLACE FUNCTION touch_series_tasks()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE tasks SET version = version
+    WHERE series_id = NEW.id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER touch_series_tasks
+AFTER UPDATE ON task_series
+FOR EACH ROW
+WHEN (
+    OLD.frequency IS DISTINCT FROM NEW.frequency OR
+    OLD.every IS DISTINCT FROM NEW.every OR
+    OLD.weekdays IS DISTINCT FROM NEW.weekdays OR
+    OLD.month_day IS DISTINCT FROM NEW.month_day
+)
+EXECUTE FUNCTION touch_series_tasks();
@@ -0,0 +1,18 @@
+-- Add migration script here
+-- A deleted account's feed URL must stop working with it.
+CREATE OR REPLACE FUNCTION set_user_inactive()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE users
+    SET state = 'inactive'
+    WHERE id = OLD.id;
+
+    DELETE FROM sessions
+    WHERE user_id = OLD.id;
+
+    DELETE FROM feed_tokens
+    WHERE user_id = OLD.id;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
\ No newline at end of file
@@ -0,0 +1,180 @@
+use roxmltree::Document;
+
+
+pub const NS_DAV: &str = "DAV:";
+pub const NS_CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+pub const NS_CALENDARSERVER: &str = "http://calendarserver.org/ns/";
+
+/// A property name, `(namespace, local name)`.
+pub type PropName = (String, String);
+
+/// Which properties a PROPFIND or REPORT asks for.
+pub enum PropRequest {
+    /// `allprop`, `propname` or an empty body.
+    All,
+    Named(Vec<PropName>)
+}
+
+pub enum ReportRequest {
+    /// Every object of the collection. Filters are not applied, clients get
+    /// a superset of what they asked for and filter it themselves.
+    CalendarQuery(PropRequest),
+    CalendarMultiget(PropRequest, Vec<String>),
+    /// A report we do not implement, like `sync-collection`.
+    Unsupported
+}
+
+/// One `response` of a multistatus: the properties found for `href` and the
+/// requested ones it does not have, or only a status.
+pub struct Response {
+    pub href: String,
+    pub found: Vec<String>,
+    pub missing: Vec<PropName>,
+    pub status: Option<u16>
+}
+
+pub fn parse_propfind(body: &str) -> Option<PropRequest> {
+    if body.trim().is_empty() {
+        return Some(PropRequest::All);
+    }
+    let document = Document::parse(body).ok()?;
+    return Some(prop_request(document.root_element()));
+}
+
+pub fn parse_report(body: &str) -> Option<ReportRequest> {
+    let document = Document::parse(body).ok()?;
+    let root = document.root_element();
+    if root.tag_name().namespace() != Some(NS_CALDAV) {
+        return Some(ReportRequest::Unsupported);
+    }
+    match root.tag_name().name() {
+        "calendar-query" => return Some(ReportRequest::CalendarQuery(prop_request(root))),
+        "calendar-multiget" => {
+            let hrefs = root.children()
+                .filter(|node| is(node, NS_DAV, "href"))
+                .filter_map(|node| node.text())
+                .map(|href| href.trim().to_string())
+                .collect();
+            return Some(ReportRequest::CalendarMultiget(prop_request(root), hrefs));
+        }
+        _ => return Some(ReportRequest::Unsupported)
+    }
+}
+
+fn prop_request(root: roxmltree::Node) -> PropRequest {
+    let Some(prop) = root.children().find(|node| is(node, NS_DAV, "prop")) else {
+        return PropRequest::All;
+    };
+    return PropRequest::Named(
+        prop.children()
+            .filter(|node| node.is_element())
+            .map(|node| (
+                node.tag_name().namespace().unwrap_or("").to_string(),
+                node.tag_name().name().to_string()
+            ))
+            .collect()
+    );
+}
+
+fn is(node: &roxmltree::Node, namespace: &str, name: &str) -> bool {
+    return node.is_element() &&
+        node.tag_name().namespace() == Some(namespace) &&
+        node.tag_name().name() == name;
+}
+
+/// Renders the `multistatus` document answering a PROPFIND or REPORT.
+pub fn multistatus(responses: &[Response]) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="utf-8"?>"#, "\n",
+        r#"<D:multistatus xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav" xmlns:CS="http://calendarserver.org/ns/">"#
+    ));
+    for response in responses {
+        xml.push_str("<D:response>");
+        xml.push_str(&format!("<D:href>{}</D:href>", escape(&response.href)));
+        if let Some(status) = response.status {
+            xml.push_str(&status_line(status));
+        }
+        if !response.found.is_empty() {
+            xml.push_str("<D:propstat><D:prop>");
+            for prop in &response.found {
+                xml.push_str(prop);
+            }
+            xml.push_str("</D:prop>");
+            xml.push_str(&status_line(200));
+            xml.push_str("</D:propstat>");
+        }
+        if !response.missing.is_empty() {
+            xml.push_str("<D:propstat><D:prop>");
+            for (namespace, name) in &response.missing {
+                xml.push_str(&format!("<X:{} xmlns:X=\"{}\"/>", name, escape(namespace)));
+            }
+            xml.push_str("</D:prop>");
+            xml.push_str(&status_line(404));
+            xml.push_str("</D:propstat>");
+        }
+        xml.push_str("</D:response>");
+    }
+    xml.push_str("</D:multistatus>\n");
+    return xml;
+}
+
+fn status_line(status: u16) -> String {
+    let reason = match status {
+        200 => "OK",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => ""
+    };
+    return format!("<D:status>HTTP/1.1 {} {}</D:status>", status, reason);
+}
+
+/// Percent-encodes a path segment for an `href`.
+pub fn encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~@".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    return encoded;
+}
+
+/// Reverses `encode`, and any other percent-encoding a client used.
+pub fn decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            if let Some(byte) = hex {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    return String::from_utf8_lossy(&decoded).to_string();
+}
+
+/// Escapes text and attribute content.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\r' => escaped.push_str("&#13;"),
+            _ => escaped.push(c)
+        }
+    }
+    return escaped;
+}
\ No newline at end of file
@@ -18,11 +18,14 @@ pub enum AppError {
     Unauthorized,
     BadRequest,
     NotFoundData,
+    Forbidden,
+    PreconditionFailed,
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, message) = match self {
+impl AppError {
+    /// The status and message sent for this error.
+    pub fn parts(self) -> (StatusCode, String) {
+        match self {
             AppError::ValidationError(err) => (StatusCode::BAD_REQUEST, err),
             AppError::UserFound => (StatusCode::FOUND, "User already registered!".to_string()),
             AppError::InternalServer => (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error!".to_string()),
@@ -30,8 +33,16 @@ impl IntoResponse for AppError {
             AppError::NotFoundUser => (StatusCode::NOT_FOUND, "User NOT found!".to_string()),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized!".to_string()),
             AppError::BadRequest => (StatusCode::BAD_REQUEST, "Bad Request".to_string()),
-            AppError::NotFoundData => (StatusCode::NOT_FOUND, "Data NOT found!".to_string())
-        };
+            AppError::NotFoundData => (StatusCode::NOT_FOUND, "Data NOT found!".to_string()),
+            AppError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden!".to_string()),
+            AppError::PreconditionFailed => (StatusCode::PRECONDITION_FAILED, "Task was changed by someone else!".to_string())
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, message) = self.parts();
         (
             status,
             Json(json!({
@@ -0,0 +1,148 @@
+use axum::{
+    extract::{
+        Multipart,
+        Path
+    },
+    http::{
+        header,
+        StatusCode
+    },
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use tracing::error;
+
+use crate::{
+    error,
+    modules::{
+        self,
+        attachment::UploadDto
+    },
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::attachment::get_all(
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(attachments) => return (StatusCode::OK, Json(attachments)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+/// Expects a `multipart/form-data` body with the file in a `file` field.
+pub async fn create(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    mut multipart: Multipart
+) -> impl IntoResponse {
+    let mut upload_dto: Option<UploadDto> = None;
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                error!("{:#?}", e);
+                return error::AppError::BadRequest.into_response();
+            }
+        };
+        if field.name() != Some("file") {
+            continue;
+        }
+        let name = field.file_name().unwrap_or("").to_string();
+        let mime_type = field.content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let data = match field.bytes().await {
+            Ok(data) => data,
+            Err(e) => {
+                error!("{:#?}", e);
+                return error::AppError::ValidationError(
+                    "file: too large or malformed".to_string()
+                ).into_response();
+            }
+        };
+        upload_dto = Some(UploadDto { name, mime_type, data: data.to_vec() });
+    }
+    let upload_dto = match upload_dto {
+        Some(upload_dto) => upload_dto,
+        None => return error::AppError::ValidationError(
+            "file: is required".to_string()
+        ).into_response()
+    };
+    if upload_dto.name.is_empty() || upload_dto.name.len() > 255 {
+        return error::AppError::ValidationError(
+            "file: name must be min=1, max=255".to_string()
+        ).into_response();
+    }
+    if upload_dto.data.is_empty() {
+        return error::AppError::ValidationError(
+            "file: must not be empty".to_string()
+        ).into_response();
+    }
+    let create_result = services::attachment::create(
+        upload_dto,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(attachment) => return (StatusCode::CREATED, Json(attachment)).into_response(),
+        Err(e) => return e.into_response()
+    };
+}
+
+pub async fn download(
+    Path((task_id, id)): Path<(i32, i32)>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let download_result = services::attachment::download(
+        id,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match download_result {
+        Ok((attachment, data)) => {
+            // Quotes and control characters would break out of the header value.
+            let file_name: String = attachment.name.chars()
+                .filter(|c| !c.is_control() && *c != '"' && *c != '\\')
+                .collect();
+            return (
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, attachment.mime_type),
+                    (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name)),
+                    (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string())
+                ],
+                data
+            ).into_response();
+        }
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete(
+    Path((task_id, id)): Path<(i32, i32)>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::attachment::delete(
+        id,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
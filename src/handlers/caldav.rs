@@ -0,0 +1,450 @@
+use axum::{
+    extract::Path,
+    http::{
+        header,
+        HeaderMap,
+        Method,
+        StatusCode
+    },
+    response::{
+        IntoResponse,
+        Redirect,
+        Response
+    },
+    Extension,
+    Json
+};
+
+use crate::{
+    dav::{
+        self,
+        PropRequest,
+        ReportRequest,
+        NS_CALDAV,
+        NS_CALENDARSERVER,
+        NS_DAV
+    },
+    error::AppError,
+    handlers::task::if_match,
+    modules::{
+        feed::FeedTask,
+        user::User
+    },
+    services,
+    ical,
+    db::get_pool
+};
+
+
+const DAV_ROOT: &str = "/api/v1/dav";
+
+/// Each resource of the tree a user sees:
+/// `/`, `/principals/<username>/`, `/calendars/<username>/`,
+/// `/calendars/<username>/tasks/` and the objects inside it.
+enum Resource<'a> {
+    Root,
+    Principal,
+    Home,
+    Calendar(&'a str),
+    Object(&'a FeedTask)
+}
+
+/// Properties listed for `allprop`. `calendar-data` is only sent on request.
+const ALL_PROPS: [(&str, &str); 13] = [
+    (NS_DAV, "resourcetype"),
+    (NS_DAV, "displayname"),
+    (NS_DAV, "current-user-principal"),
+    (NS_DAV, "principal-URL"),
+    (NS_DAV, "owner"),
+    (NS_DAV, "supported-report-set"),
+    (NS_DAV, "current-user-privilege-set"),
+    (NS_DAV, "getetag"),
+    (NS_DAV, "getcontenttype"),
+    (NS_CALDAV, "calendar-home-set"),
+    (NS_CALDAV, "calendar-user-address-set"),
+    (NS_CALDAV, "supported-calendar-component-set"),
+    (NS_CALENDARSERVER, "getctag")
+];
+
+pub async fn create_token(
+    Extension(user): Extension<User>
+) -> impl IntoResponse {
+    let create_result = services::caldav::create_token(
+        &user,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(caldav_token) => return (StatusCode::CREATED, Json(caldav_token)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete_token(
+    Extension(user): Extension<User>
+) -> impl IntoResponse {
+    let deleted_result = services::caldav::delete_token(
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+/// Clients are often only given the server's address.
+pub async fn well_known() -> impl IntoResponse {
+    return Redirect::permanent(&format!("{}/", DAV_ROOT));
+}
+
+pub async fn root(
+    method: Method,
+    Extension(user): Extension<User>,
+    body: String
+) -> Response {
+    match method.as_str() {
+        "OPTIONS" => return options(),
+        "PROPFIND" => return propfind(&user, &body, vec![(format!("{}/", DAV_ROOT), Resource::Root)]),
+        _ => return StatusCode::METHOD_NOT_ALLOWED.into_response()
+    }
+}
+
+pub async fn principal(
+    method: Method,
+    Path(username): Path<String>,
+    Extension(user): Extension<User>,
+    body: String
+) -> Response {
+    if username != user.username {
+        return AppError::NotFoundData.into_response();
+    }
+    match method.as_str() {
+        "OPTIONS" => return options(),
+        "PROPFIND" => return propfind(&user, &body, vec![(principal_href(&user), Resource::Principal)]),
+        _ => return StatusCode::METHOD_NOT_ALLOWED.into_response()
+    }
+}
+
+pub async fn home(
+    method: Method,
+    Path(username): Path<String>,
+    Extension(user): Extension<User>,
+    headers: HeaderMap,
+    body: String
+) -> Response {
+    if username != user.username {
+        return AppError::NotFoundData.into_response();
+    }
+    match method.as_str() {
+        "OPTIONS" => return options(),
+        "PROPFIND" => {
+            let mut resources = vec![(home_href(&user), Resource::Home)];
+            let ctag;
+            if depth(&headers) > 0 {
+                ctag = match services::caldav::ctag(user.id, &get_pool().await).await {
+                    Ok(ctag) => ctag,
+                    Err(e) => return e.into_response()
+                };
+                resources.push((calendar_href(&user), Resource::Calendar(&ctag)));
+            }
+            return propfind(&user, &body, resources);
+        }
+        _ => return StatusCode::METHOD_NOT_ALLOWED.into_response()
+    }
+}
+
+pub async fn calendar(
+    method: Method,
+    Path(username): Path<String>,
+    Extension(user): Extension<User>,
+    headers: HeaderMap,
+    body: String
+) -> Response {
+    if username != user.username {
+        return AppError::NotFoundData.into_response();
+    }
+    let pool = get_pool().await;
+    match method.as_str() {
+        "OPTIONS" => return options(),
+        "PROPFIND" => {
+            let ctag = match services::caldav::ctag(user.id, &pool).await {
+                Ok(ctag) => ctag,
+                Err(e) => return e.into_response()
+            };
+            let tasks = if depth(&headers) > 0 {
+                match services::caldav::get_all(user.id, &pool).await {
+                    Ok(tasks) => tasks,
+                    Err(e) => return e.into_response()
+                }
+            } else {
+                vec![]
+            };
+            let mut resources = vec![(calendar_href(&user), Resource::Calendar(&ctag))];
+            for task in &tasks {
+                resources.push((object_href(&user, task), Resource::Object(task)));
+            }
+            return propfind(&user, &body, resources);
+        }
+        "REPORT" => {
+            let Some(report) = dav::parse_report(&body) else {
+                return StatusCode::BAD_REQUEST.into_response();
+            };
+            let tasks = match services::caldav::get_all(user.id, &pool).await {
+                Ok(tasks) => tasks,
+                Err(e) => return e.into_response()
+            };
+            match report {
+                ReportRequest::CalendarQuery(prop_request) => {
+                    let props = report_props(&prop_request);
+                    let responses = tasks.iter()
+                        .map(|task| response(&user, object_href(&user, task), &Resource::Object(task), &props))
+                        .collect::<Vec<dav::Response>>();
+                    return multistatus(&responses);
+                }
+                ReportRequest::CalendarMultiget(prop_request, hrefs) => {
+                    let props = report_props(&prop_request);
+                    let responses = hrefs.iter()
+                        .map(|href| {
+                            let name = dav::decode(href.rsplit('/').next().unwrap_or(""));
+                            match tasks.iter().find(|task| task.name == name) {
+                                Some(task) => response(&user, href.clone(), &Resource::Object(task), &props),
+                                None => dav::Response {
+                                    href: href.clone(),
+                                    found: vec![],
+                                    missing: vec![],
+                                    status: Some(404)
+                                }
+                            }
+                        })
+                        .collect::<Vec<dav::Response>>();
+                    return multistatus(&responses);
+                }
+                ReportRequest::Unsupported => return (
+                    StatusCode::FORBIDDEN,
+                    [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+                    r#"<?xml version="1.0" encoding="utf-8"?><D:error xmlns:D="DAV:"><D:supported-report/></D:error>"#
+                ).into_response()
+            }
+        }
+        _ => return StatusCode::METHOD_NOT_ALLOWED.into_response()
+    }
+}
+
+pub async fn object(
+    method: Method,
+    Path((username, name)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    headers: HeaderMap,
+    body: String
+) -> Response {
+    if username != user.username {
+        return AppError::NotFoundData.into_response();
+    }
+    let pool = get_pool().await;
+    match method.as_str() {
+        "OPTIONS" => return options(),
+        "GET" | "HEAD" => {
+            match services::caldav::get_one(&name, user.id, &pool).await {
+                Ok(task) => return (
+                    StatusCode::OK,
+                    [
+                        (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+                        (header::ETAG, etag(&task))
+                    ],
+                    ical::calendar(std::slice::from_ref(&task))
+                ).into_response(),
+                Err(e) => return e.into_response()
+            }
+        }
+        "PROPFIND" => {
+            match services::caldav::get_one(&name, user.id, &pool).await {
+                Ok(task) => return propfind(&user, &body, vec![(object_href(&user, &task), Resource::Object(&task))]),
+                Err(e) => return e.into_response()
+            }
+        }
+        "PUT" => {
+            let version = match if_match(&headers) {
+                Ok(version) => version,
+                Err(e) => return e.into_response()
+            };
+            let create_only = headers.get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.trim() == "*");
+            // No ETag is sent back: the stored object is not byte-for-byte the
+            // one received, so clients must fetch it again (RFC 4791 5.3.4).
+            match services::caldav::put(&name, &body, version, create_only, &user, &pool).await {
+                Ok(true) => return StatusCode::CREATED.into_response(),
+                Ok(false) => return StatusCode::NO_CONTENT.into_response(),
+                Err(e) => return e.into_response()
+            }
+        }
+        "DELETE" => {
+            let version = match if_match(&headers) {
+                Ok(version) => version,
+                Err(e) => return e.into_response()
+            };
+            match services::caldav::delete(&name, version, user.id, &pool).await {
+                Ok(_) => return StatusCode::NO_CONTENT.into_response(),
+                Err(e) => return e.into_response()
+            }
+        }
+        _ => return StatusCode::METHOD_NOT_ALLOWED.into_response()
+    }
+}
+
+fn options() -> Response {
+    return (
+        StatusCode::OK,
+        [
+            ("DAV", "1, 3, calendar-access"),
+            ("Allow", "OPTIONS, GET, HEAD, PUT, DELETE, PROPFIND, REPORT")
+        ]
+    ).into_response();
+}
+
+fn propfind(user: &User, body: &str, resources: Vec<(String, Resource)>) -> Response {
+    let Some(prop_request) = dav::parse_propfind(body) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let (props, all) = match prop_request {
+        PropRequest::All => (all_props(), true),
+        PropRequest::Named(names) => (names, false)
+    };
+    let responses: Vec<dav::Response> = resources.into_iter()
+        .map(|(href, resource)| {
+            let mut response = response(user, href, &resource, &props);
+            // Properties a resource kind never has are left out of `allprop`.
+            if all {
+                response.missing.clear();
+            }
+            response
+        })
+        .collect();
+    return multistatus(&responses);
+}
+
+/// A REPORT without `prop` gets the ETag and the data of each object.
+fn report_props(prop_request: &PropRequest) -> Vec<dav::PropName> {
+    match prop_request {
+        PropRequest::All => return vec![
+            (NS_DAV.to_string(), "getetag".to_string()),
+            (NS_CALDAV.to_string(), "calendar-data".to_string())
+        ],
+        PropRequest::Named(names) => return names.clone()
+    }
+}
+
+fn all_props() -> Vec<dav::PropName> {
+    return ALL_PROPS.iter()
+        .map(|(namespace, name)| (namespace.to_string(), name.to_string()))
+        .collect();
+}
+
+fn response(user: &User, href: String, resource: &Resource, props: &[dav::PropName]) -> dav::Response {
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+    for (namespace, name) in props {
+        match prop(user, resource, namespace, name) {
+            Some(value) => found.push(value),
+            None => missing.push((namespace.clone(), name.clone()))
+        }
+    }
+    return dav::Response {
+        href,
+        found,
+        missing,
+        status: None
+    };
+}
+
+/// The XML of property `name` on `resource`, `None` when it has none.
+fn prop(user: &User, resource: &Resource, namespace: &str, name: &str) -> Option<String> {
+    let principal = format!("<D:href>{}</D:href>", dav::escape(&principal_href(user)));
+    match (namespace, name, resource) {
+        (NS_DAV, "resourcetype", Resource::Root | Resource::Home) =>
+            return Some("<D:resourcetype><D:collection/></D:resourcetype>".to_string()),
+        (NS_DAV, "resourcetype", Resource::Principal) =>
+            return Some("<D:resourcetype><D:collection/><D:principal/></D:resourcetype>".to_string()),
+        (NS_DAV, "resourcetype", Resource::Calendar(_)) =>
+            return Some("<D:resourcetype><D:collection/><C:calendar/></D:resourcetype>".to_string()),
+        (NS_DAV, "resourcetype", Resource::Object(_)) =>
+            return Some("<D:resourcetype/>".to_string()),
+        (NS_DAV, "displayname", Resource::Principal) =>
+            return Some(format!("<D:displayname>{}</D:displayname>", dav::escape(&user.name))),
+        (NS_DAV, "displayname", Resource::Calendar(_)) =>
+            return Some("<D:displayname>Tasks</D:displayname>".to_string()),
+        (NS_DAV, "current-user-principal", _) =>
+            return Some(format!("<D:current-user-principal>{}</D:current-user-principal>", principal)),
+        (NS_DAV, "principal-URL", Resource::Principal) =>
+            return Some(format!("<D:principal-URL>{}</D:principal-URL>", principal)),
+        (NS_DAV, "owner", Resource::Calendar(_) | Resource::Object(_)) =>
+            return Some(format!("<D:owner>{}</D:owner>", principal)),
+        (NS_DAV, "supported-report-set", Resource::Calendar(_)) => return Some(concat!(
+            "<D:supported-report-set>",
+            "<D:supported-report><D:report><C:calendar-query/></D:report></D:supported-report>",
+            "<D:supported-report><D:report><C:calendar-multiget/></D:report></D:supported-report>",
+            "</D:supported-report-set>"
+        ).to_string()),
+        (NS_DAV, "current-user-privilege-set", Resource::Calendar(_) | Resource::Object(_)) => return Some(concat!(
+            "<D:current-user-privilege-set>",
+            "<D:privilege><D:read/></D:privilege>",
+            "<D:privilege><D:write/></D:privilege>",
+            "<D:privilege><D:write-content/></D:privilege>",
+            "<D:privilege><D:bind/></D:privilege>",
+            "<D:privilege><D:unbind/></D:privilege>",
+            "</D:current-user-privilege-set>"
+        ).to_string()),
+        (NS_DAV, "getetag", Resource::Object(task)) =>
+            return Some(format!("<D:getetag>{}</D:getetag>", dav::escape(&etag(task)))),
+        (NS_DAV, "getcontenttype", Resource::Object(_)) =>
+            return Some("<D:getcontenttype>text/calendar; charset=utf-8; component=VTODO</D:getcontenttype>".to_string()),
+        (NS_CALDAV, "calendar-home-set", Resource::Principal) =>
+            return Some(format!("<C:calendar-home-set><D:href>{}</D:href></C:calendar-home-set>", dav::escape(&home_href(user)))),
+        (NS_CALDAV, "calendar-user-address-set", Resource::Principal) =>
+            return Some(format!("<C:calendar-user-address-set><D:href>mailto:{}</D:href></C:calendar-user-address-set>", dav::escape(&user.email))),
+        (NS_CALDAV, "supported-calendar-component-set", Resource::Calendar(_)) =>
+            return Some("<C:supported-calendar-component-set><C:comp name=\"VTODO\"/></C:supported-calendar-component-set>".to_string()),
+        (NS_CALDAV, "calendar-data", Resource::Object(task)) =>
+            return Some(format!("<C:calendar-data>{}</C:calendar-data>", dav::escape(&ical::calendar(std::slice::from_ref(*task))))),
+        (NS_CALENDARSERVER, "getctag", Resource::Calendar(ctag)) =>
+            return Some(format!("<CS:getctag>{}</CS:getctag>", ctag)),
+        _ => return None
+    }
+}
+
+fn multistatus(responses: &[dav::Response]) -> Response {
+    return (
+        StatusCode::MULTI_STATUS,
+        [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+        dav::multistatus(responses)
+    ).into_response();
+}
+
+/// `Depth` of a PROPFIND; a missing one means infinity, served as 1.
+fn depth(headers: &HeaderMap) -> u8 {
+    match headers.get("Depth").and_then(|value| value.to_str().ok()) {
+        Some("0") => return 0,
+        _ => return 1
+    }
+}
+
+/// Same tag as the task API, so both sides agree on versions.
+fn etag(task: &FeedTask) -> String {
+    return format!("\"{}\"", task.version);
+}
+
+fn principal_href(user: &User) -> String {
+    return format!("{}/principals/{}/", DAV_ROOT, dav::encode(&user.username));
+}
+
+fn home_href(user: &User) -> String {
+    return format!("{}/calendars/{}/", DAV_ROOT, dav::encode(&user.username));
+}
+
+fn calendar_href(user: &User) -> String {
+    return format!("{}tasks/", home_href(user));
+}
+
+fn object_href(user: &User, task: &FeedTask) -> String {
+    return format!("{}{}", calendar_href(user), dav::encode(&task.name));
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+use axum::{
+    extract::Path,
+    http::{
+        header,
+        HeaderMap,
+        StatusCode
+    },
+    response::IntoResponse,
+    Extension,
+    Json
+};
+
+use crate::{
+    handlers::task::{
+        etag,
+        if_match
+    },
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::checkbox::get_all(
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(checkboxes) => return (StatusCode::OK, Json(checkboxes)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn toggle(
+    Path((task_id, index)): Path<(i32, usize)>,
+    Extension(user): Extension<modules::user::User>,
+    headers: HeaderMap,
+    Json(toggle_dto): Json<modules::checkbox::ToggleDto>
+) -> impl IntoResponse {
+    let version = match if_match(&headers) {
+        Ok(version) => version,
+        Err(e) => return e.into_response()
+    };
+    let toggled_result = services::checkbox::toggle(
+        toggle_dto,
+        index,
+        task_id,
+        user.id,
+        version,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match toggled_result {
+        Ok(task) => return (StatusCode::OK, [(header::ETAG, etag(&task))], Json(task)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
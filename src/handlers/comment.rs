@@ -0,0 +1,88 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::comment::get_all(
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(comments) => return (StatusCode::OK, Json(comments)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn create(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(create_dto): Json<modules::comment::CreateDto>
+) -> impl IntoResponse {
+    if let Err(err) = create_dto.validate() {
+        return error::AppError::ValidationError(err.to_string()).into_response();
+    }
+    let create_result = services::comment::create(
+        create_dto,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(comment) => return (StatusCode::CREATED, Json(comment)).into_response(),
+        Err(e) => return e.into_response()
+    };
+}
+
+pub async fn update(
+    Path((task_id, id)): Path<(i32, i32)>,
+    Extension(user): Extension<modules::user::User>,
+    Json(update_dto): Json<modules::comment::UpdateDto>
+) -> impl IntoResponse {
+    if let Err(e) = update_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let updated_result = services::comment::update(
+        update_dto,
+        id,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match updated_result {
+        Ok(comment) => return (StatusCode::OK, Json(comment)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete(
+    Path((task_id, id)): Path<(i32, i32)>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::comment::delete(
+        id,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
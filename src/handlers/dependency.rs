@@ -0,0 +1,67 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::dependency::get_all(
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(tasks) => return (StatusCode::OK, Json(tasks)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn create(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(create_dto): Json<modules::dependency::CreateDto>
+) -> impl IntoResponse {
+    if let Err(err) = create_dto.validate() {
+        return error::AppError::ValidationError(err.to_string()).into_response();
+    }
+    let create_result = services::dependency::create(
+        create_dto,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(tasks) => return (StatusCode::CREATED, Json(tasks)).into_response(),
+        Err(e) => return e.into_response()
+    };
+}
+
+pub async fn delete(
+    Path((task_id, blocked_by_id)): Path<(i32, i32)>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::dependency::delete(
+        blocked_by_id,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use axum::{
+    extract::Path,
+    http::{
+        header,
+        StatusCode
+    },
+    response::IntoResponse,
+    Extension,
+    Json
+};
+
+use crate::{
+    error,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn create_token(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let create_result = services::feed::create_token(
+        user.id,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(feed_token) => return (StatusCode::CREATED, Json(feed_token)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete_token(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::feed::delete_token(
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+/// Public: calendar apps can not send the session cookie, so the token in
+/// the path is the only credential.
+pub async fn calendar(
+    Path(file_name): Path<String>
+) -> impl IntoResponse {
+    let token = match file_name.strip_suffix(".ics") {
+        Some(token) => token,
+        None => return error::AppError::NotFoundData.into_response()
+    };
+    let render_result = services::feed::render(
+        token,
+        &get_pool().await
+    ).await;
+    match render_result {
+        Ok(calendar) => return (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+                (header::CACHE_CONTROL, "private, no-store")
+            ],
+            calendar
+        ).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+
+use crate::{
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::history::get_all(
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(entries) => return (StatusCode::OK, Json(entries)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+use axum::{
+    extract::{
+        Path,
+        Query
+    },
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::project::get_all(
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(projects) => return (StatusCode::OK, Json(projects)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn create(
+    Extension(user): Extension<modules::user::User>,
+    Json(create_dto): Json<modules::project::CreateDto>
+) -> impl IntoResponse {
+    if let Err(err) = create_dto.validate() {
+        return error::AppError::ValidationError(err.to_string()).into_response();
+    }
+    let create_result = services::project::create(
+        create_dto,
+        user.id,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(project) => return (StatusCode::CREATED, Json(project)).into_response(),
+        Err(e) => return e.into_response()
+    };
+}
+
+pub async fn update(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(update_dto): Json<modules::project::UpdateDto>
+) -> impl IntoResponse {
+    if let Err(e) = update_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let updated_result = services::project::update(
+        update_dto,
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match updated_result {
+        Ok(project) => return (StatusCode::OK, Json(project)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Query(delete_query): Query<modules::project::DeleteQuery>
+) -> impl IntoResponse {
+    let deleted_result = services::project::delete(
+        id,
+        user.id,
+        delete_query.tasks.unwrap_or(modules::project::DeleteMode::Inbox),
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn set(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(recurrence_dto): Json<modules::recurrence::RecurrenceDto>
+) -> impl IntoResponse {
+    if let Err(e) = recurrence_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let set_result = services::recurrence::set(
+        recurrence_dto,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match set_result {
+        Ok(task) => return (StatusCode::OK, Json(task)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn stop(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let stop_result = services::recurrence::stop(
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match stop_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,162 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules::{
+        self,
+        share::Target
+    },
+    services,
+    db::get_pool
+};
+
+
+pub async fn invite_task(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(invite_dto): Json<modules::share::InviteDto>
+) -> impl IntoResponse {
+    return invite(Target::Task(task_id), user, invite_dto).await;
+}
+
+pub async fn invite_project(
+    Path(project_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(invite_dto): Json<modules::share::InviteDto>
+) -> impl IntoResponse {
+    return invite(Target::Project(project_id), user, invite_dto).await;
+}
+
+async fn invite(
+    target: Target,
+    user: modules::user::User,
+    invite_dto: modules::share::InviteDto
+) -> axum::response::Response {
+    if let Err(err) = invite_dto.validate() {
+        return error::AppError::ValidationError(err.to_string()).into_response();
+    }
+    let invite_result = services::share::invite(
+        invite_dto,
+        target,
+        user.id,
+        &get_pool().await
+    ).await;
+    match invite_result {
+        Ok(membership) => return (StatusCode::CREATED, Json(membership)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn get_task_members(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    return get_members(Target::Task(task_id), user).await;
+}
+
+pub async fn get_project_members(
+    Path(project_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    return get_members(Target::Project(project_id), user).await;
+}
+
+async fn get_members(
+    target: Target,
+    user: modules::user::User
+) -> axum::response::Response {
+    let get_result = services::share::get_members(
+        target,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(memberships) => return (StatusCode::OK, Json(memberships)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn get_invitations(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::share::get_invitations(
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(memberships) => return (StatusCode::OK, Json(memberships)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn accept(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let accepted_result = services::share::accept(
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match accepted_result {
+        Ok(membership) => return (StatusCode::OK, Json(membership)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn decline(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let declined_result = services::share::decline(
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match declined_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn update(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(update_dto): Json<modules::share::UpdateDto>
+) -> impl IntoResponse {
+    if let Err(e) = update_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let updated_result = services::share::update(
+        update_dto,
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match updated_result {
+        Ok(membership) => return (StatusCode::OK, Json(membership)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::share::delete(
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,35 @@
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get(
+    Extension(user): Extension<modules::user::User>,
+    Query(stats_query): Query<modules::stats::StatsQuery>
+) -> impl IntoResponse {
+    if let Err(e) = stats_query.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let get_result = services::stats::get(
+        stats_query,
+        user.id,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(stats) => return (StatusCode::OK, Json(stats)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::tag::get_all(
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(tags) => return (StatusCode::OK, Json(tags)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn create(
+    Extension(user): Extension<modules::user::User>,
+    Json(create_dto): Json<modules::tag::CreateDto>
+) -> impl IntoResponse {
+    if let Err(err) = create_dto.validate() {
+        return error::AppError::ValidationError(err.to_string()).into_response();
+    }
+    let create_result = services::tag::create(
+        create_dto,
+        user.id,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(tag) => return (StatusCode::CREATED, Json(tag)).into_response(),
+        Err(e) => return e.into_response()
+    };
+}
+
+pub async fn update(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(update_dto): Json<modules::tag::UpdateDto>
+) -> impl IntoResponse {
+    if let Err(e) = update_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let updated_result = services::tag::update(
+        update_dto,
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match updated_result {
+        Ok(tag) => return (StatusCode::OK, Json(tag)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::tag::delete(
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -1,6 +1,13 @@
 use axum::{
-    extract::Path, 
-    http::StatusCode, 
+    extract::{
+        Path,
+        Query
+    }, 
+    http::{
+        header,
+        HeaderMap,
+        StatusCode
+    }, 
     response::IntoResponse, 
     Extension, 
     Json
@@ -16,14 +23,81 @@ use crate::{
 
 
 pub async fn get_all(
-    Extension(user): Extension<modules::user::User>
+    Extension(user): Extension<modules::user::User>,
+    Query(query_dto): Query<modules::task::QueryDto>
 ) -> impl IntoResponse {
+    if let Err(e) = query_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let html = query_dto.html == Some(true);
     let get_result = services::task::get_all(
+        query_dto,
         user.id, 
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(mut tasks) => {
+            if html {
+                tasks.data.iter_mut().for_each(|task| task.render_body());
+            }
+            return (StatusCode::OK, Json(tasks)).into_response();
+        }
+        Err(e) => return e.into_response()
+    }
+}
+
+/// Answers `304 Not Modified` when `If-None-Match` still holds the task's
+/// current `ETag`.
+pub async fn get_one(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Query(read_dto): Query<modules::task::ReadDto>,
+    headers: HeaderMap
+) -> impl IntoResponse {
+    let get_result = services::task::get_one(
+        id,
+        user.id,
         &get_pool().await
     ).await;
     match get_result {
-        Ok(tasks) => return (StatusCode::OK, Json(tasks)).into_response(),
+        Ok(mut task) => {
+            // The rendered body is another representation of the same
+            // version, so it gets a tag of its own.
+            let html = read_dto.html == Some(true);
+            let etag = if html { format!("\"{}-html\"", task.version) } else { etag(&task) };
+            let not_modified = headers.get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.split(',').any(|tag| {
+                    let tag = tag.trim();
+                    tag == "*" || tag.trim_start_matches("W/") == etag
+                }));
+            if not_modified {
+                return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+            }
+            if html {
+                task.render_body();
+            }
+            return (StatusCode::OK, [(header::ETAG, etag)], Json(task)).into_response();
+        }
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn search(
+    Extension(user): Extension<modules::user::User>,
+    Query(search_dto): Query<modules::task::SearchDto>
+) -> impl IntoResponse {
+    if let Err(e) = search_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let search_result = services::task::search(
+        search_dto,
+        user.id,
+        &get_pool().await
+    ).await;
+    match search_result {
+        Ok(results) => return (StatusCode::OK, Json(results)).into_response(),
         Err(e) => return e.into_response()
     }
 }
@@ -38,45 +112,180 @@ pub async fn create(
     let create_result = services::task::create(
         create_dto, 
         user.id, 
+        &user.timezone,
         &get_pool().await
     ).await;
     match create_result {
-        Ok(task) => return (StatusCode::CREATED, Json(task)).into_response(),
+        Ok(task) => return (StatusCode::CREATED, [(header::ETAG, etag(&task))], Json(task)).into_response(),
         Err(e) => return e.into_response()
     };
 }
 
+pub async fn clone(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(clone_dto): Json<modules::task::CloneDto>
+) -> impl IntoResponse {
+    let clone_result = services::task::clone(
+        clone_dto,
+        id,
+        user.id,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match clone_result {
+        Ok(task) => return (StatusCode::CREATED, [(header::ETAG, etag(&task))], Json(task)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
 pub async fn update(
     Path(id): Path<i32>,
     Extension(user): Extension<modules::user::User>,
+    headers: HeaderMap,
     Json(update_dto): Json<modules::task::UpdateDto>
 ) -> impl IntoResponse {
     if let Err(e) = update_dto.validate() {
         return error::AppError::ValidationError(e.to_string()).into_response();
     }
+    let version = match if_match(&headers) {
+        Ok(version) => version,
+        Err(e) => return e.into_response()
+    };
     let updated_result = services::task::udpate(
         update_dto, 
         id, 
         user.id, 
+        version,
+        &user.timezone,
         &get_pool().await
     ).await;
     match updated_result {
-        Ok(task) => return (StatusCode::OK, Json(task)).into_response(),
+        Ok(task) => return (StatusCode::OK, [(header::ETAG, etag(&task))], Json(task)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn reorder(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    headers: HeaderMap,
+    Json(reorder_dto): Json<modules::task::ReorderDto>
+) -> impl IntoResponse {
+    if let Err(e) = reorder_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let version = match if_match(&headers) {
+        Ok(version) => version,
+        Err(e) => return e.into_response()
+    };
+    let reordered_result = services::task::reorder(
+        reorder_dto,
+        id,
+        user.id,
+        version,
+        &get_pool().await
+    ).await;
+    match reordered_result {
+        Ok(task) => return (StatusCode::OK, [(header::ETAG, etag(&task))], Json(task)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn bulk(
+    Extension(user): Extension<modules::user::User>,
+    Json(bulk_dto): Json<modules::task::BulkDto>
+) -> impl IntoResponse {
+    if let Err(e) = bulk_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let bulk_result = services::task::bulk(
+        bulk_dto,
+        user.id,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match bulk_result {
+        Ok(report) => return (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn move_to_project(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    headers: HeaderMap,
+    Json(move_dto): Json<modules::task::MoveDto>
+) -> impl IntoResponse {
+    if let Err(e) = move_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let version = match if_match(&headers) {
+        Ok(version) => version,
+        Err(e) => return e.into_response()
+    };
+    let moved_result = services::task::move_to_project(
+        move_dto,
+        id,
+        user.id,
+        version,
+        &get_pool().await
+    ).await;
+    match moved_result {
+        Ok(task) => return (StatusCode::OK, [(header::ETAG, etag(&task))], Json(task)).into_response(),
         Err(e) => return e.into_response()
     }
 }
 
 pub async fn delete(
     Path(id): Path<i32>,
-    Extension(user): Extension<modules::user::User>
+    Extension(user): Extension<modules::user::User>,
+    headers: HeaderMap
 ) -> impl IntoResponse {
+    let version = match if_match(&headers) {
+        Ok(version) => version,
+        Err(e) => return e.into_response()
+    };
     let deleted_result = services::task::delete(
         id, 
         user.id, 
+        version,
         &get_pool().await
     ).await;
     match deleted_result {
         Ok(_) => return (StatusCode::OK).into_response(),
         Err(e) => return e.into_response()
     }
+}
+
+pub fn etag(task: &modules::task::Task) -> String {
+    return format!("\"{}\"", task.version);
+}
+
+/// Versions listed by the `If-Match` header, any of which may match; `None`
+/// when the header is missing or `*`. Weak tags compare like strong ones
+/// and the tag of the HTML representation names the same version. Entries
+/// that are not one of our tags can never match.
+pub fn if_match(headers: &HeaderMap) -> Result<Option<Vec<i32>>, error::AppError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let value = value.to_str().unwrap_or("").trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    let versions: Vec<i32> = value.split(',')
+        .filter_map(|tag| {
+            let tag = tag.trim();
+            tag.strip_prefix("W/").unwrap_or(tag)
+                .trim_matches('"')
+                .trim_end_matches("-html")
+                .parse::<i32>()
+                .ok()
+        })
+        .collect();
+    if versions.is_empty() {
+        return Err(error::AppError::PreconditionFailed);
+    }
+    return Ok(Some(versions));
 }
\ No newline at end of file
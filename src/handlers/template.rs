@@ -0,0 +1,119 @@
+use axum::{
+    extract::Path,
+    http::{
+        header,
+        StatusCode
+    },
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    handlers::task::etag,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::template::get_all(
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(templates) => return (StatusCode::OK, Json(templates)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn get_one(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::template::get_one(
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(template) => return (StatusCode::OK, Json(template)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn create(
+    Extension(user): Extension<modules::user::User>,
+    Json(create_dto): Json<modules::template::CreateDto>
+) -> impl IntoResponse {
+    if let Err(err) = create_dto.validate() {
+        return error::AppError::ValidationError(err.to_string()).into_response();
+    }
+    let create_result = services::template::create(
+        create_dto,
+        user.id,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(template) => return (StatusCode::CREATED, Json(template)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn update(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(update_dto): Json<modules::template::UpdateDto>
+) -> impl IntoResponse {
+    if let Err(e) = update_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let updated_result = services::template::update(
+        update_dto,
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match updated_result {
+        Ok(template) => return (StatusCode::OK, Json(template)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::template::delete(
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn instantiate(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(instantiate_dto): Json<modules::template::InstantiateDto>
+) -> impl IntoResponse {
+    let instantiate_result = services::template::instantiate(
+        instantiate_dto,
+        id,
+        user.id,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match instantiate_result {
+        Ok(task) => return (StatusCode::CREATED, [(header::ETAG, etag(&task))], Json(task)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
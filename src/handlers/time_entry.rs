@@ -0,0 +1,178 @@
+use axum::{
+    extract::{
+        Path,
+        Query
+    },
+    http::{
+        header,
+        StatusCode
+    },
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules::{
+        self,
+        time_entry::ReportFormat
+    },
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::time_entry::get_all(
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(task_time) => return (StatusCode::OK, Json(task_time)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn start(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(start_dto): Json<modules::time_entry::StartDto>
+) -> impl IntoResponse {
+    if let Err(e) = start_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let start_result = services::time_entry::start(
+        start_dto,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match start_result {
+        Ok(entry) => return (StatusCode::CREATED, Json(entry)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn stop(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let stop_result = services::time_entry::stop(
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match stop_result {
+        Ok(entry) => return (StatusCode::OK, Json(entry)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn running(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::time_entry::running(
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(entry) => return (StatusCode::OK, Json(entry)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn create(
+    Path(task_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(create_dto): Json<modules::time_entry::CreateDto>
+) -> impl IntoResponse {
+    if let Err(e) = create_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let create_result = services::time_entry::create(
+        create_dto,
+        task_id,
+        user.id,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(entry) => return (StatusCode::CREATED, Json(entry)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn update(
+    Path((task_id, id)): Path<(i32, i32)>,
+    Extension(user): Extension<modules::user::User>,
+    Json(update_dto): Json<modules::time_entry::UpdateDto>
+) -> impl IntoResponse {
+    if let Err(e) = update_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let updated_result = services::time_entry::update(
+        update_dto,
+        id,
+        task_id,
+        user.id,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match updated_result {
+        Ok(entry) => return (StatusCode::OK, Json(entry)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete(
+    Path((task_id, id)): Path<(i32, i32)>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::time_entry::delete(
+        id,
+        task_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn report(
+    Extension(user): Extension<modules::user::User>,
+    Query(report_query): Query<modules::time_entry::ReportQuery>
+) -> impl IntoResponse {
+    if let Err(e) = report_query.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let format = report_query.format.unwrap_or(ReportFormat::Json);
+    let report_result = services::time_entry::report(
+        report_query,
+        user.id,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    let report = match report_result {
+        Ok(report) => report,
+        Err(e) => return e.into_response()
+    };
+    match format {
+        ReportFormat::Json => return (StatusCode::OK, Json(report)).into_response(),
+        ReportFormat::Csv => return (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"time-report.csv\"".to_string())
+            ],
+            services::time_entry::report_csv(&report)
+        ).into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,66 @@
+use axum::{
+    body::Body,
+    extract::Query,
+    http::{
+        header,
+        StatusCode
+    },
+    response::IntoResponse,
+    Extension,
+    Json
+};
+
+use crate::{
+    modules::{
+        self,
+        transfer::Format
+    },
+    services,
+    db::get_pool
+};
+
+
+pub async fn export(
+    Extension(user): Extension<modules::user::User>,
+    Query(export_query): Query<modules::transfer::ExportQuery>
+) -> impl IntoResponse {
+    let format = export_query.format.unwrap_or(Format::Json);
+    let (content_type, file_name) = match format {
+        Format::Json => ("application/json", "tasks.json"),
+        Format::Csv => ("text/csv; charset=utf-8", "tasks.csv"),
+        Format::TodoTxt => ("text/plain; charset=utf-8", "todo.txt")
+    };
+    let stream = services::transfer::export(
+        format,
+        user.id,
+        user.timezone,
+        get_pool().await
+    );
+    return (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name))
+        ],
+        Body::from_stream(stream)
+    ).into_response();
+}
+
+pub async fn import(
+    Extension(user): Extension<modules::user::User>,
+    Query(import_query): Query<modules::transfer::ImportQuery>,
+    data: String
+) -> impl IntoResponse {
+    let import_result = services::transfer::import(
+        &data,
+        import_query.format.unwrap_or(Format::Json),
+        import_query.dry_run.unwrap_or(false),
+        user.id,
+        &user.timezone,
+        &get_pool().await
+    ).await;
+    match import_result {
+        Ok(report) => return (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
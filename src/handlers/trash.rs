@@ -0,0 +1,56 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use serde_json::json;
+
+use crate::{
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::trash::get_all(
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(tasks) => return (StatusCode::OK, Json(tasks)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn restore(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let restored_result = services::trash::restore(
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match restored_result {
+        Ok(task) => return (StatusCode::OK, Json(task)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn empty(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let emptied_result = services::trash::empty(
+        user.id,
+        &get_pool().await
+    ).await;
+    match emptied_result {
+        Ok(purged) => return (StatusCode::OK, Json(json!({ "purged": purged }))).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -137,7 +137,8 @@ pub async fn update_information(
     }
     if  update_info_dto.email.is_none() &&
         update_info_dto.name.is_none()  &&
-        update_info_dto.username.is_none() {
+        update_info_dto.username.is_none() &&
+        update_info_dto.timezone.is_none() {
         return error::AppError::BadRequest.into_response();
     }
     let pool = get_pool().await;
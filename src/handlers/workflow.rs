@@ -0,0 +1,117 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json
+};
+use validator::Validate;
+
+use crate::{
+    error,
+    modules,
+    services,
+    db::get_pool
+};
+
+
+pub async fn get_all(
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::workflow::get_all(
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(workflows) => return (StatusCode::OK, Json(workflows)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn create(
+    Extension(user): Extension<modules::user::User>,
+    Json(create_dto): Json<modules::workflow::CreateDto>
+) -> impl IntoResponse {
+    if let Err(err) = create_dto.validate() {
+        return error::AppError::ValidationError(err.to_string()).into_response();
+    }
+    let create_result = services::workflow::create(
+        create_dto,
+        user.id,
+        &get_pool().await
+    ).await;
+    match create_result {
+        Ok(workflow) => return (StatusCode::CREATED, Json(workflow)).into_response(),
+        Err(e) => return e.into_response()
+    };
+}
+
+pub async fn update(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(update_dto): Json<modules::workflow::UpdateDto>
+) -> impl IntoResponse {
+    if let Err(e) = update_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let updated_result = services::workflow::update(
+        update_dto,
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match updated_result {
+        Ok(workflow) => return (StatusCode::OK, Json(workflow)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn delete(
+    Path(id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let deleted_result = services::workflow::delete(
+        id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match deleted_result {
+        Ok(_) => return (StatusCode::OK).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn get_for_project(
+    Path(project_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>
+) -> impl IntoResponse {
+    let get_result = services::workflow::get_for_project(
+        project_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match get_result {
+        Ok(workflow) => return (StatusCode::OK, Json(workflow)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
+
+pub async fn set_for_project(
+    Path(project_id): Path<i32>,
+    Extension(user): Extension<modules::user::User>,
+    Json(set_dto): Json<modules::workflow::SetDto>
+) -> impl IntoResponse {
+    if let Err(e) = set_dto.validate() {
+        return error::AppError::ValidationError(e.to_string()).into_response();
+    }
+    let set_result = services::workflow::set_for_project(
+        set_dto,
+        project_id,
+        user.id,
+        &get_pool().await
+    ).await;
+    match set_result {
+        Ok(workflow) => return (StatusCode::OK, Json(workflow)).into_response(),
+        Err(e) => return e.into_response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,269 @@
+use crate::modules::{
+    feed::FeedTask,
+    workflow::StateKind
+};
+
+
+/// Lines longer than this many octets are folded (RFC 5545 3.1).
+const MAX_LINE_OCTETS: usize = 75;
+
+/// One content line of a component, `NAME;PARAM=VALUE:value`. Names and
+/// parameter names are upper-cased, the value is still escaped.
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        return self.params.iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str());
+    }
+}
+
+/// A VCALENDAR holding one VTODO per task.
+pub fn calendar(tasks: &[FeedTask]) -> String {
+    let mut calendar = String::new();
+    push_line(&mut calendar, "BEGIN:VCALENDAR");
+    push_line(&mut calendar, "VERSION:2.0");
+    push_line(&mut calendar, "PRODID:-//ToDoListify//Tasks//EN");
+    push_line(&mut calendar, "CALSCALE:GREGORIAN");
+    push_line(&mut calendar, "X-WR-CALNAME:ToDoListify");
+    for task in tasks {
+        push_vtodo(&mut calendar, task);
+    }
+    push_line(&mut calendar, "END:VCALENDAR");
+    return calendar;
+}
+
+fn push_vtodo(calendar: &mut String, task: &FeedTask) {
+    push_line(calendar, "BEGIN:VTODO");
+    push_line(calendar, &format!("UID:{}", escape_text(&task.uid)));
+    push_line(calendar, &format!("DTSTAMP:{}", task.updated_at));
+    push_line(calendar, &format!("CREATED:{}", task.created_at));
+    push_line(calendar, &format!("LAST-MODIFIED:{}", task.updated_at));
+    push_line(calendar, &format!("SEQUENCE:{}", task.version - 1));
+    push_line(calendar, &format!("SUMMARY:{}", escape_text(&task.title)));
+    if !task.body.is_empty() {
+        push_line(calendar, &format!("DESCRIPTION:{}", escape_text(&task.body)));
+    }
+    if let Some(start_at) = &task.start_at {
+        push_line(calendar, &format!("DTSTART:{}", start_at));
+    }
+    if let Some(due_at) = &task.due_at {
+        push_line(calendar, &format!("DUE:{}", due_at));
+    }
+    push_line(calendar, &format!("STATUS:{}", status(task)));
+    if task.closed {
+        push_line(calendar, "PERCENT-COMPLETE:100");
+    }
+    push_line(calendar, &format!("PRIORITY:{}", priority(&task.priority)));
+    if !task.tags.is_empty() {
+        let tags: Vec<String> = task.tags.iter().map(|tag| escape_text(tag)).collect();
+        push_line(calendar, &format!("CATEGORIES:{}", tags.join(",")));
+    }
+    push_line(calendar, "END:VTODO");
+}
+
+fn status(task: &FeedTask) -> &'static str {
+    if task.closed {
+        return "COMPLETED";
+    }
+    if task.started {
+        return "IN-PROCESS";
+    }
+    return "NEEDS-ACTION";
+}
+
+/// The kind of task state for a VTODO `STATUS`. A cancelled to-do needs no
+/// more action, so it counts as closed.
+pub fn state(status: &str) -> StateKind {
+    match status.to_ascii_uppercase().as_str() {
+        "IN-PROCESS" => return StateKind::Active,
+        "COMPLETED" | "CANCELLED" => return StateKind::Closed,
+        _ => return StateKind::Initial
+    }
+}
+
+/// 1 is the highest iCalendar priority and 9 the lowest.
+fn priority(priority: &str) -> u8 {
+    match priority {
+        "HIGH" => return 1,
+        "LOW" => return 9,
+        _ => return 5
+    }
+}
+
+/// The task priority for a VTODO `PRIORITY`; 0 means undefined.
+pub fn task_priority(priority: &str) -> &'static str {
+    match priority.trim().parse::<u8>() {
+        Ok(1..=4) => return "HIGH",
+        Ok(6..=9) => return "LOW",
+        _ => return "MEDIUM"
+    }
+}
+
+/// Escapes a TEXT value (RFC 5545 3.3.11).
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c)
+        }
+    }
+    return escaped;
+}
+
+pub fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\')
+        }
+    }
+    return unescaped;
+}
+
+/// A DATE or DATE-TIME value in the form the task DTOs accept:
+/// `20250801` becomes `2025-08-01` and `20250801T100000Z`
+/// `2025-08-01T10:00:00Z`. A DATE-TIME without `Z` stays floating.
+pub fn date_time(value: &str) -> Option<String> {
+    let value = value.trim();
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None)
+    };
+    if date.len() != 8 || !date.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let date = format!("{}-{}-{}", &date[0..4], &date[4..6], &date[6..8]);
+    let Some(time) = time else {
+        return Some(date);
+    };
+    let (time, utc) = match time.strip_suffix('Z') {
+        Some(time) => (time, "Z"),
+        None => (time, "")
+    };
+    if time.len() != 6 || !time.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    return Some(format!(
+        "{}T{}:{}:{}{}",
+        date,
+        &time[0..2],
+        &time[2..4],
+        &time[4..6],
+        utc
+    ));
+}
+
+/// Properties of the first `component` in `data`, without those of the
+/// components nested in it, like a VALARM inside a VTODO.
+pub fn parse_component(data: &str, component: &str) -> Option<Vec<Property>> {
+    let mut properties = Vec::new();
+    let mut depth = 0;
+    let mut found = false;
+    for line in unfold(data) {
+        let Some(property) = parse_line(&line) else {
+            continue;
+        };
+        let value = property.value.to_ascii_uppercase();
+        if property.name == "BEGIN" {
+            if found {
+                depth += 1;
+            } else if value == component {
+                found = true;
+            }
+            continue;
+        }
+        if property.name == "END" {
+            if found && depth == 0 && value == component {
+                return Some(properties);
+            }
+            if found && depth > 0 {
+                depth -= 1;
+            }
+            continue;
+        }
+        if found && depth == 0 {
+            properties.push(property);
+        }
+    }
+    return None;
+}
+
+/// Joins folded lines back together.
+fn unfold(data: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in data.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(rest) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    return lines;
+}
+
+fn parse_line(line: &str) -> Option<Property> {
+    // The value starts at the first ':' outside a quoted parameter value.
+    let mut quoted = false;
+    let mut colon = None;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => {
+                colon = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let colon = colon?;
+    let mut parts = line[..colon].split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| (name.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    return Some(Property {
+        name,
+        params,
+        value: line[colon + 1..].to_string()
+    });
+}
+
+/// Appends `line` with CRLF, folding it so no line exceeds `MAX_LINE_OCTETS`
+/// without splitting a UTF-8 character.
+fn push_line(calendar: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            calendar.push_str("\r\n ");
+            // The leading space of the continuation counts towards its length.
+            octets = 1;
+        }
+        calendar.push(c);
+        octets += c.len_utf8();
+    }
+    calendar.push_str("\r\n");
+}
\ No newline at end of file
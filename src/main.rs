@@ -1,8 +1,10 @@
 use axum::{
     http::{
+        header,
         HeaderValue,
         Method
     }, 
+    routing::any,
     Router
 };
 use dotenvy::dotenv;
@@ -16,6 +18,11 @@ mod modules;
 mod error;
 mod db;
 mod services;
+mod storage;
+mod ical;
+mod dav;
+mod todotxt;
+mod markdown;
 
 #[tokio::main]
 async fn main() {
@@ -25,11 +32,30 @@ async fn main() {
         .expect(">>> TODOLISTIFY_APP_FRONTEND_URL NOT found!");
     let cors_layer = CorsLayer::new()
         .allow_origin(frontend_url.parse::<HeaderValue>().unwrap())
-        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE]);
+        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
+        .allow_headers([header::CONTENT_TYPE, header::IF_MATCH, header::IF_NONE_MATCH])
+        .expose_headers([header::ETAG]);
     let app = Router::new()
         .nest("/api/v1", routes::main())
-        .layer(axum::middleware::from_fn(middlewares::logger::log_request))
-        .layer(cors_layer);
+        .layer(cors_layer)
+        .nest("/api/v1", routes::dav())
+        .route("/.well-known/caldav", any(handlers::caldav::well_known))
+        .layer(axum::middleware::from_fn(middlewares::logger::log_request));
+    let retention_days = std::env::var("TODOLISTIFY_TRASH_RETENTION_DAYS")
+        .unwrap_or("30".to_string())
+        .parse::<i32>()
+        .expect(">>> TODOLISTIFY_TRASH_RETENTION_DAYS must be a number of days!");
+    tokio::spawn(async move {
+        let pool = db::get_pool().await;
+        loop {
+            if let Ok(purged) = services::trash::purge_expired(retention_days, &pool).await {
+                if purged > 0 {
+                    info!("Purged {} task(s) from the trash", purged);
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(60 * 60)).await;
+        }
+    });
     let port = std::env::var("TODOLISTIFY_APP_PORT")
         .expect(">>> TODOLISTIFY_APP_PORT NOT found!");
     let listener = tokio::net::TcpListener::bind(
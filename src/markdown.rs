@@ -0,0 +1,72 @@
+use std::ops::Range;
+
+use ammonia::Builder;
+use pulldown_cmark::{
+    html::push_html,
+    Event,
+    Options,
+    Parser
+};
+
+use crate::modules::checkbox::Checkbox;
+
+
+fn options() -> Options {
+    return Options::ENABLE_TASKLISTS |
+        Options::ENABLE_TABLES |
+        Options::ENABLE_STRIKETHROUGH;
+}
+
+/// Renders a task body as CommonMark. Raw HTML in the body is kept by the
+/// parser and then stripped by the sanitizer, along with scripts, event
+/// handlers and `javascript:` links.
+pub fn html(body: &str) -> String {
+    let mut html = String::with_capacity(body.len() * 2);
+    push_html(&mut html, Parser::new_ext(body, options()));
+    return Builder::default()
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .clean(&html)
+        .to_string();
+}
+
+/// The `- [ ]` and `- [x]` task list items of a body, in document order.
+/// Markers inside code blocks are not items, so they are not listed.
+pub fn checkboxes(body: &str) -> Vec<Checkbox> {
+    return markers(body)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (checked, range))| {
+            let line_end = body[range.end..].find('\n')
+                .map(|offset| range.end + offset)
+                .unwrap_or(body.len());
+            return Checkbox {
+                index,
+                checked,
+                text: body[range.end..line_end].trim().to_string()
+            };
+        })
+        .collect();
+}
+
+/// The body with the marker of checkbox `index` set to `checked`, `None`
+/// when there is no such checkbox. Nothing else of the body is touched.
+pub fn toggle(body: &str, index: usize, checked: bool) -> Option<String> {
+    let (_, range) = markers(body).into_iter().nth(index)?;
+    let mut toggled = String::with_capacity(body.len());
+    toggled.push_str(&body[..range.start]);
+    toggled.push_str(if checked { "[x]" } else { "[ ]" });
+    toggled.push_str(&body[range.end..]);
+    return Some(toggled);
+}
+
+/// Each task list marker with its state and the byte range of its `[ ]`.
+fn markers(body: &str) -> Vec<(bool, Range<usize>)> {
+    return Parser::new_ext(body, options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::TaskListMarker(checked) => Some((checked, range)),
+            _ => None
+        })
+        .collect();
+}
\ No newline at end of file
@@ -1,12 +1,23 @@
 use axum::{
     extract::Request, 
+    http::{
+        header,
+        StatusCode
+    },
     middleware::Next, 
     response::IntoResponse
 };
 use axum_extra::extract::cookie::CookieJar;
+use base64::{
+    engine::general_purpose::STANDARD,
+    Engine
+};
 use crate::{
     error::AppError,
-    services::auth::get_user_by_session,
+    services::{
+        auth::get_user_by_session,
+        caldav::get_user_by_token
+    },
     db::get_pool
 };
 
@@ -38,4 +49,34 @@ pub async fn auth_guard(
         }
         None => return AppError::Unauthorized.into_response()
     }
+}
+
+/// HTTP Basic authentication with the account's username and its CalDAV
+/// token, for clients that can not keep our session cookie. The account
+/// password is never accepted here: it would be stored by every client and
+/// checked with Argon2 on each request.
+pub async fn basic_guard(
+    mut req: Request,
+    next: Next
+) -> impl IntoResponse {
+    let credentials = req.headers().get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|value| STANDARD.decode(value.trim()).ok())
+        .and_then(|value| String::from_utf8(value).ok());
+    if let Some((username, token)) = credentials.as_deref()
+        .and_then(|credentials| credentials.split_once(':')) {
+        match get_user_by_token(username, token, &get_pool().await).await {
+            Ok(user) => {
+                req.extensions_mut().insert(user);
+                return next.run(req).await;
+            }
+            Err(AppError::InternalServer) => return AppError::InternalServer.into_response(),
+            Err(_) => {}
+        }
+    }
+    return (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Basic realm=\"ToDoListify\", charset=\"UTF-8\"")]
+    ).into_response();
 }
\ No newline at end of file
@@ -0,0 +1,32 @@
+use serde::{
+    Deserialize, 
+    Serialize
+};
+
+
+/// Largest single upload accepted, in bytes.
+pub const MAX_ATTACHMENT_SIZE: usize = 25 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct Attachment {
+    pub id: i32,
+    pub task_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub size: i64,
+    pub mime_type: String,
+    pub checksum: String,
+    pub created_at: Option<String>,
+
+    /// Only loaded to read the blob, never exposed.
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub storage_key: String
+}
+
+/// A file read from the multipart body, before it is stored.
+pub struct UploadDto {
+    pub name: String,
+    pub mime_type: String,
+    pub data: Vec<u8>
+}
\ No newline at end of file
@@ -0,0 +1,28 @@
+use serde::Serialize;
+
+use crate::modules::workflow::StateKind;
+
+/// A VTODO sent by a CalDAV client, mapped onto task fields. Properties we
+/// have no field for, like alarms or RRULE, are dropped.
+pub struct VTodo {
+    pub uid: String,
+    pub title: String,
+    pub body: Option<String>,
+    /// Resolved to a state of the task's workflow when stored.
+    pub state: StateKind,
+    pub priority: String,
+    pub start_at: Option<String>,
+    pub due_at: Option<String>,
+    /// `TZID` of `DUE` or `DTSTART`, for their floating times.
+    pub tzid: Option<String>
+}
+
+/// Returned once when the token is created, to be used as the password of
+/// the CalDAV account. Only its hash is stored, so a lost token can only be
+/// replaced.
+#[derive(Serialize)]
+pub struct CaldavToken {
+    pub username: String,
+    pub token: String,
+    pub url: String
+}
\ No newline at end of file
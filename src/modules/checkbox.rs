@@ -0,0 +1,20 @@
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+
+/// A `- [ ]` line of a task body. `index` counts the checkboxes of the body
+/// from 0, in document order.
+#[derive(Serialize)]
+pub struct Checkbox {
+    pub index: usize,
+    pub checked: bool,
+    pub text: String
+}
+
+/// Sets the checkbox to `checked`, or flips it when `checked` is missing.
+#[derive(Deserialize)]
+pub struct ToggleDto {
+    pub checked: Option<bool>
+}
\ No newline at end of file
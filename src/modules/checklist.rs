@@ -0,0 +1,36 @@
+use serde::{
+    Deserialize, 
+    Serialize
+};
+use validator::Validate;
+
+
+#[derive(Serialize, sqlx::FromRow, Clone)]
+pub struct ChecklistItem {
+    pub id: i32,
+    pub task_id: i32,
+    pub title: String,
+    pub done: bool,
+    pub position: i32,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>
+}
+
+#[derive(Validate, Deserialize)]
+pub struct CreateDto {
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub title: String,
+
+    pub done: Option<bool>,
+}
+
+#[derive(Validate, Deserialize)]
+pub struct UpdateDto {
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub title: Option<String>,
+
+    pub done: Option<bool>,
+
+    #[validate(range(min=0, message="min=0"))]
+    pub position: Option<i32>
+}
\ No newline at end of file
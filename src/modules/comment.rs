@@ -0,0 +1,29 @@
+use serde::{
+    Deserialize, 
+    Serialize
+};
+use validator::Validate;
+
+
+#[derive(Serialize, sqlx::FromRow, Clone)]
+pub struct Comment {
+    pub id: i32,
+    pub task_id: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub body: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>
+}
+
+#[derive(Validate, Deserialize)]
+pub struct CreateDto {
+    #[validate(length(min=1, max=6000, message="min=1, max=6000"))]
+    pub body: String
+}
+
+#[derive(Validate, Deserialize)]
+pub struct UpdateDto {
+    #[validate(length(min=1, max=6000, message="min=1, max=6000"))]
+    pub body: String
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use validator::Validate;
+
+
+#[derive(Validate, Deserialize)]
+pub struct CreateDto {
+    #[validate(range(min=1, message="min=1"))]
+    pub blocked_by_id: i32
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+use serde::Serialize;
+use sqlx::types::Json;
+
+/// Returned once when the token is created. Only its hash is stored, so a
+/// lost token can only be replaced.
+#[derive(Serialize)]
+pub struct FeedToken {
+    pub token: String,
+    pub url: String
+}
+
+/// A task as rendered into the feed and served over CalDAV. Timestamps are
+/// already in the iCalendar UTC form `YYYYMMDDTHHMMSSZ`.
+#[derive(sqlx::FromRow)]
+pub struct FeedTask {
+    pub id: i32,
+    /// Resource name inside the CalDAV collection.
+    pub name: String,
+    pub uid: String,
+    pub title: String,
+    pub body: String,
+    pub closed: bool,
+    /// Past the first state of its workflow, and not closed.
+    pub started: bool,
+    pub priority: String,
+    pub version: i32,
+    pub start_at: Option<String>,
+    pub due_at: Option<String>,
+    pub tags: Json<Vec<String>>,
+    pub created_at: String,
+    pub updated_at: String
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+use serde::Serialize;
+use sqlx::types::Json;
+
+
+/// One create, update or delete of a task. `changes` maps each changed
+/// column to its `old` and `new` values.
+#[derive(Serialize, sqlx::FromRow, Clone)]
+pub struct HistoryEntry {
+    pub id: i32,
+    pub task_id: i32,
+    pub user_id: Option<i32>,
+    pub username: Option<String>,
+    pub action: String,
+    pub changes: Json<serde_json::Value>,
+    pub created_at: Option<String>
+}
\ No newline at end of file
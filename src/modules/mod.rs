@@ -1,2 +1,19 @@
 pub mod user;
-pub mod task;
\ No newline at end of file
+pub mod task;
+pub mod project;
+pub mod tag;
+pub mod checklist;
+pub mod recurrence;
+pub mod share;
+pub mod comment;
+pub mod attachment;
+pub mod history;
+pub mod dependency;
+pub mod transfer;
+pub mod feed;
+pub mod caldav;
+pub mod checkbox;
+pub mod stats;
+pub mod workflow;
+pub mod time_entry;
+pub mod template;
\ No newline at end of file
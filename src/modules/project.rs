@@ -0,0 +1,45 @@
+use serde::{
+    Deserialize, 
+    Serialize
+};
+use validator::Validate;
+
+
+#[derive(Serialize, sqlx::FromRow, Clone)]
+pub struct Project {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub archived: bool,
+    /// `None` while the project follows the owner's default workflow.
+    pub workflow_id: Option<i32>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>
+}
+
+#[derive(Validate, Deserialize)]
+pub struct CreateDto {
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub name: String,
+}
+
+#[derive(Validate, Deserialize)]
+pub struct UpdateDto {
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub name: Option<String>,
+
+    pub archived: Option<bool>
+}
+
+/// What happens to the tasks of a deleted project.
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteMode {
+    Cascade,
+    Inbox
+}
+
+#[derive(Deserialize)]
+pub struct DeleteQuery {
+    pub tasks: Option<DeleteMode>
+}
\ No newline at end of file
@@ -0,0 +1,149 @@
+use chrono::{
+    Datelike,
+    Duration,
+    Months,
+    NaiveDate,
+    NaiveDateTime
+};
+use serde::{
+    Deserialize,
+    Serialize
+};
+use validator::{
+    Validate,
+    ValidationError
+};
+
+
+/// The recurrence rule of a series, as returned inline with each `Task`.
+#[derive(Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct Recurrence {
+    pub frequency: String,
+    pub every: i32,
+    pub weekdays: Option<Vec<i32>>,
+    pub month_day: Option<i32>
+}
+
+/// A completed occurrence of a series, with its dates local to the owner
+/// of the task.
+#[derive(sqlx::FromRow)]
+pub struct Occurrence {
+    #[sqlx(flatten)]
+    pub rule: Recurrence,
+    pub timezone: String,
+    pub due_local: Option<NaiveDateTime>,
+    pub start_local: Option<NaiveDateTime>,
+    pub now_local: NaiveDateTime
+}
+
+#[derive(Validate, Deserialize)]
+#[validate(schema(function = "recurrence_validate"))]
+pub struct RecurrenceDto {
+    #[validate(custom(function = "frequency_validate"))]
+    pub frequency: String,
+
+    #[validate(range(min=1, max=365, message="min=1, max=365"))]
+    pub every: Option<i32>,
+
+    /// ISO weekdays, 1 = Monday .. 7 = Sunday. Required for `WEEKLY`.
+    #[validate(length(min=1, max=7, message="min=1, max=7"))]
+    pub weekdays: Option<Vec<i32>>,
+
+    /// Day of the month, clamped to the last day of shorter months.
+    #[validate(range(min=1, max=31, message="min=1, max=31"))]
+    pub month_day: Option<i32>
+}
+
+/// Which occurrences an update of a recurring task applies to.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    This,
+    Future
+}
+
+/// Returns the local date-time of the first occurrence following `base`
+/// that is later than `completed`, the local completion time. Finishing an
+/// overdue task therefore skips the occurrences already missed.
+pub fn next_occurrence(
+    rule: &Recurrence,
+    base: NaiveDateTime,
+    completed: NaiveDateTime
+) -> NaiveDateTime {
+    let every = rule.every.max(1);
+    if rule.frequency == "AFTER_DONE" {
+        return completed.date().and_time(base.time()) + Duration::days(every as i64);
+    }
+    let mut rule = rule.clone();
+    if rule.weekdays.as_ref().is_none_or(|weekdays| weekdays.is_empty()) {
+        rule.weekdays = Some(vec![base.weekday().number_from_monday() as i32]);
+    }
+    if rule.month_day.is_none() {
+        rule.month_day = Some(base.day() as i32);
+    }
+    let mut next = step(&rule, base, every);
+    while next <= completed {
+        next = step(&rule, next, every);
+    }
+    return next;
+}
+
+fn step(rule: &Recurrence, base: NaiveDateTime, every: i32) -> NaiveDateTime {
+    match rule.frequency.as_str() {
+        "WEEKLY" => {
+            let mut weekdays = rule.weekdays.clone().unwrap_or_default();
+            weekdays.sort();
+            let today = base.weekday().number_from_monday() as i32;
+            if let Some(day) = weekdays.iter().find(|day| **day > today) {
+                return base + Duration::days((day - today) as i64);
+            }
+            let week_start = base - Duration::days((today - 1) as i64);
+            return week_start + Duration::weeks(every as i64) + Duration::days((weekdays[0] - 1) as i64);
+        }
+        "MONTHLY" => {
+            let month_day = rule.month_day.unwrap_or(base.day() as i32) as u32;
+            let first = NaiveDate::from_ymd_opt(base.year(), base.month(), 1).unwrap()
+                + Months::new(every as u32);
+            let last_day = (first + Months::new(1)).pred_opt().unwrap().day();
+            let date = NaiveDate::from_ymd_opt(
+                first.year(),
+                first.month(),
+                month_day.min(last_day)
+            ).unwrap();
+            return date.and_time(base.time());
+        }
+        _ => return base + Duration::days(every as i64)
+    }
+}
+
+fn frequency_validate(frequency: &str) -> Result<(), ValidationError> {
+    if  frequency != "DAILY"   &&
+        frequency != "WEEKLY"  &&
+        frequency != "MONTHLY" &&
+        frequency != "AFTER_DONE" {
+        return Err(
+            ValidationError::new(
+                "frequency must be one of ('DAILY', 'WEEKLY', 'MONTHLY', 'AFTER_DONE')"
+            )
+        );
+    }
+    return Ok(());
+}
+
+fn recurrence_validate(recurrence: &RecurrenceDto) -> Result<(), ValidationError> {
+    if let Some(weekdays) = &recurrence.weekdays {
+        if weekdays.iter().any(|day| *day < 1 || *day > 7) {
+            return Err(ValidationError::new("weekdays must be between 1 (Monday) and 7 (Sunday)"));
+        }
+    }
+    if recurrence.frequency == "WEEKLY" && recurrence.weekdays.is_none() {
+        return Err(ValidationError::new("weekdays is required for WEEKLY"));
+    }
+    if recurrence.frequency != "WEEKLY" && recurrence.weekdays.is_some() {
+        return Err(ValidationError::new("weekdays is only allowed for WEEKLY"));
+    }
+    if recurrence.frequency != "MONTHLY" && recurrence.month_day.is_some() {
+        return Err(ValidationError::new("month_day is only allowed for MONTHLY"));
+    }
+    return Ok(());
+}
\ No newline at end of file
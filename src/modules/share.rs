@@ -0,0 +1,67 @@
+use serde::{
+    Deserialize, 
+    Serialize
+};
+use validator::{
+    Validate, 
+    ValidationError
+};
+
+
+/// Minimum access needed for an action, matching the `rank` column of the
+/// `task_access` and `project_access` views.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Rank {
+    Viewer = 1,
+    Editor = 2,
+    Owner = 3
+}
+
+/// What a membership grants access to.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Target {
+    Task(i32),
+    Project(i32)
+}
+
+#[derive(Serialize, sqlx::FromRow, Clone)]
+pub struct Membership {
+    pub id: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub task_id: Option<i32>,
+    pub project_id: Option<i32>,
+    pub role: String,
+    pub state: String,
+    pub invited_by: Option<i32>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>
+}
+
+#[derive(Validate, Deserialize)]
+pub struct InviteDto {
+    #[validate(length(min=3, max=255, message="min=3, max=255"))]
+    pub username: String,
+
+    #[validate(custom(function = "role_validate"))]
+    pub role: String,
+}
+
+#[derive(Validate, Deserialize)]
+pub struct UpdateDto {
+    #[validate(custom(function = "role_validate"))]
+    pub role: String
+}
+
+fn role_validate(role: &str) -> Result<(), ValidationError> {
+    if  role != "VIEWER" &&
+        role != "EDITOR" &&
+        role != "OWNER" {
+        return Err(
+            ValidationError::new(
+                "role must be one of ('VIEWER', 'EDITOR', 'OWNER')"
+            )
+        );
+    }
+    return Ok(());
+}
\ No newline at end of file
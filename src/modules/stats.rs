@@ -0,0 +1,85 @@
+use chrono::NaiveDate;
+use serde::{
+    Deserialize,
+    Serialize
+};
+use validator::{
+    Validate,
+    ValidationError
+};
+
+
+/// The longest range of days the completion series covers.
+pub const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    Day,
+    Week
+}
+
+#[derive(Validate, Deserialize)]
+pub struct StatsQuery {
+    /// First day of the completion series in the user's timezone, 29 days
+    /// before `to` by default.
+    #[validate(custom(function = "date_validate"))]
+    pub from: Option<String>,
+
+    /// Last day of the completion series, today by default.
+    #[validate(custom(function = "date_validate"))]
+    pub to: Option<String>,
+
+    /// Weeks start on Monday. Defaults to `day`.
+    pub bucket: Option<Bucket>,
+
+    /// Only counts the tasks of this project.
+    #[validate(range(min=1, message="min=1"))]
+    pub project_id: Option<i32>
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct StateCount {
+    pub state: String,
+    pub count: i64
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct PriorityCount {
+    pub priority: String,
+    pub count: i64
+}
+
+/// Tasks completed in the bucket starting on `date`. The first week can
+/// start before `from`, but only counts the days of the range.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct CompletedCount {
+    pub date: String,
+    pub count: i64
+}
+
+/// Counts cover the live tasks the user can see. Completions, the average
+/// and the streaks are counted in days of the user's timezone.
+#[derive(Serialize)]
+pub struct Stats {
+    pub from: String,
+    pub to: String,
+    pub bucket: Bucket,
+    pub total: i64,
+    pub by_state: Vec<StateCount>,
+    pub by_priority: Vec<PriorityCount>,
+    pub completed: Vec<CompletedCount>,
+    /// Mean time from creation to a closed state of the tasks completed
+    /// in the range, `None` when there are none.
+    pub average_seconds_to_done: Option<f64>,
+    /// Days in a row, up to today or yesterday, with a completed task.
+    pub current_streak: i64,
+    pub longest_streak: i64
+}
+
+pub fn date_validate(date: &str) -> Result<(), ValidationError> {
+    if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+        return Err(ValidationError::new("date must be 'YYYY-MM-DD'"));
+    }
+    return Ok(());
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+use regex::Regex;
+use serde::{
+    Deserialize, 
+    Serialize
+};
+use validator::{
+    Validate, 
+    ValidationError
+};
+
+
+#[derive(Serialize, sqlx::FromRow, Clone)]
+pub struct Tag {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub color: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>
+}
+
+#[derive(Validate, Deserialize)]
+pub struct CreateDto {
+    #[validate(length(min=1, max=64, message="min=1, max=64"))]
+    pub name: String,
+
+    #[validate(custom(function = "color_validate"))]
+    pub color: Option<String>,
+}
+
+#[derive(Validate, Deserialize)]
+pub struct UpdateDto {
+    #[validate(length(min=1, max=64, message="min=1, max=64"))]
+    pub name: Option<String>,
+
+    #[validate(custom(function = "color_validate"))]
+    pub color: Option<String>
+}
+
+fn color_validate(color: &str) -> Result<(), ValidationError> {
+    let pattren = Regex::new(r"^#[0-9a-fA-F]{6}$").unwrap();
+    if !pattren.is_match(color) {
+        return Err(ValidationError::new("color must be a hex color like '#ff8800'"));
+    }
+    return Ok(());
+}
\ No newline at end of file
@@ -1,25 +1,75 @@
+use regex::Regex;
 use serde::{
     Deserialize, 
+    Deserializer,
     Serialize
 };
+use sqlx::types::Json;
 use validator::{
     Validate, 
     ValidationError
 };
 
+use crate::{
+    modules::{
+        attachment::Attachment,
+        recurrence::{
+            Recurrence,
+            RecurrenceDto,
+            Scope
+        }
+    },
+    markdown
+};
+
 
 #[derive(Serialize, sqlx::FromRow, Clone)]
 pub struct Task {
     pub id: i32,
     pub user_id: i32,
+    pub project_id: Option<i32>,
+    pub series_id: Option<i32>,
     pub title: String,
     pub body: Option<String>,
+    /// `body` rendered from CommonMark to sanitized HTML, only sent when
+    /// the request asks for it with `html=true`.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
     pub state: String,
+    /// The state is a closed one of the task's workflow.
+    pub closed: bool,
+    /// Position of the state in the workflow, for sorting by state.
+    #[serde(skip)]
+    pub state_position: i32,
     pub priority: String,
+    /// Manual position, as a decimal string to keep its full precision.
+    pub rank: String,
+    /// Moves on with every write; sent as the `ETag` of the task.
+    pub version: i32,
+    pub start_at: Option<String>,
+    pub due_at: Option<String>,
+    pub overdue: bool,
+    /// Some task this one depends on is not done yet.
+    pub blocked: bool,
+    pub tags: Json<Vec<TaskTag>>,
+    pub checklist_done: i64,
+    pub checklist_total: i64,
+    pub comment_count: i64,
+    pub attachments: Json<Vec<Attachment>>,
+    pub recurrence: Option<Json<Recurrence>>,
+    pub deleted_at: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TaskTag {
+    pub id: i32,
+    pub name: String,
+    pub color: String
+}
+
 #[derive(Validate, Deserialize)]
 pub struct CreateDto {
     #[validate(length(min=1, max=255, message="min=1, max=255"))]
@@ -29,7 +79,7 @@ pub struct CreateDto {
     pub body: Option<String>,
 
     #[validate(
-        length(min=4, max=11, message="min=4, max=11"),
+        length(min=1, max=32, message="min=1, max=32"),
         custom(function = "state_validate")
     )]
     pub state: Option<String>,
@@ -39,9 +89,24 @@ pub struct CreateDto {
         custom(function = "priority_validate")
     )]
     pub priority: Option<String>,
+
+    #[validate(custom(function = "datetime_validate"))]
+    pub start_at: Option<String>,
+
+    #[validate(custom(function = "datetime_validate"))]
+    pub due_at: Option<String>,
+
+    #[validate(range(min=1, message="min=1"))]
+    pub project_id: Option<i32>,
+
+    #[validate(length(max=50, message="max=50"))]
+    pub tag_ids: Option<Vec<i32>>,
+
+    #[validate(nested)]
+    pub recurrence: Option<RecurrenceDto>,
 }
 
-#[derive(Validate, Deserialize, Debug)]
+#[derive(Validate, Deserialize, Debug, Default)]
 pub struct UpdateDto {
     #[validate(length(min=1, max=255, message="min=1, max=255"))]
     pub title: Option<String>,
@@ -50,7 +115,7 @@ pub struct UpdateDto {
     pub body: Option<String>,
 
     #[validate(
-        length(min=4, max=11, message="min=4, max=11"),
+        length(min=1, max=32, message="min=1, max=32"),
         custom(function = "state_validate")
     )]
     pub state: Option<String>,
@@ -59,23 +124,258 @@ pub struct UpdateDto {
         length(min=3, max=6, message="min=3, max=6"),
         custom(function = "priority_validate")
     )]
-    pub priority: Option<String>
+    pub priority: Option<String>,
+
+    /// `null` clears the date.
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(custom(function = "datetime_validate"))]
+    pub start_at: Option<Option<String>>,
+
+    /// `null` clears the date.
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(custom(function = "datetime_validate"))]
+    pub due_at: Option<Option<String>>,
+
+    /// Moves the task like `MoveDto`, `null` being the inbox. Only owners
+    /// can move a task.
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(range(min=1, message="min=1"))]
+    pub project_id: Option<Option<i32>>,
+
+    /// Replaces every tag on the task; an empty list detaches all of them.
+    #[validate(length(max=50, message="max=50"))]
+    pub tag_ids: Option<Vec<i32>>,
+
+    /// When the task moves to a closed state, also tick every checklist item.
+    pub complete_checklist: Option<bool>,
+
+    /// For recurring tasks, `future` also applies the edit to later occurrences.
+    pub scope: Option<Scope>,
+
+    /// Starts or finishes the task even while it is blocked.
+    pub force: Option<bool>
+}
+
+#[derive(Validate, Deserialize)]
+pub struct SearchDto {
+    /// Words are AND-ed, `word*` matches a prefix and `"two words"` a phrase.
+    #[validate(length(min=1, max=256, message="min=1, max=256"))]
+    pub q: String,
+
+    #[validate(range(min=1, max=100, message="min=1, max=100"))]
+    pub limit: Option<i64>,
+
+    #[validate(range(min=0, message="min=0"))]
+    pub offset: Option<i64>
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub task: Task,
+    pub rank: f32,
+    pub title_highlight: String,
+    pub body_highlight: String
+}
+
+/// What a copy made by cloning changes from the original. The copy is
+/// checked by the rules of `CreateDto`, which it is created through.
+#[derive(Deserialize)]
+pub struct CloneDto {
+    /// Defaults to the title of the original.
+    pub title: Option<String>,
+
+    /// Defaults to the project of the original, `null` is the inbox.
+    #[serde(default, deserialize_with = "nullable")]
+    pub project_id: Option<Option<i32>>
+}
+
+/// Moves a task into a project, or back to the inbox when `project_id` is null.
+#[derive(Validate, Deserialize)]
+pub struct MoveDto {
+    #[validate(range(min=1, message="min=1"))]
+    pub project_id: Option<i32>
+}
+
+/// Places a task between two neighbours of the list. Given only one of
+/// them, the task goes right next to it.
+#[derive(Validate, Deserialize)]
+#[validate(schema(function = "reorder_validate"))]
+pub struct ReorderDto {
+    #[validate(range(min=1, message="min=1"))]
+    pub after_id: Option<i32>,
+
+    #[validate(range(min=1, message="min=1"))]
+    pub before_id: Option<i32>
+}
+
+/// The operation a bulk request applies to every task.
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkAction {
+    SetState,
+    SetPriority,
+    Delete,
+    Move
+}
+
+#[derive(Validate, Deserialize)]
+#[validate(schema(function = "bulk_validate"))]
+pub struct BulkDto {
+    #[validate(length(min=1, max=500, message="min=1, max=500"))]
+    pub ids: Vec<i32>,
+
+    pub action: BulkAction,
+
+    /// Required by `set_state`.
+    #[validate(custom(function = "state_validate"))]
+    pub state: Option<String>,
+
+    /// Required by `set_priority`.
+    #[validate(custom(function = "priority_validate"))]
+    pub priority: Option<String>,
+
+    /// Target of `move`; null or missing moves the tasks to the inbox.
+    #[validate(range(min=1, message="min=1"))]
+    pub project_id: Option<i32>
+}
+
+/// Outcome of a bulk operation for one task. `error` is set when
+/// `status` is not 200.
+#[derive(Serialize)]
+pub struct BulkResult {
+    pub id: i32,
+    pub status: u16,
+    pub error: Option<String>
+}
+
+#[derive(Serialize)]
+pub struct BulkReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkResult>
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    CreatedAt,
+    UpdatedAt,
+    Title,
+    State,
+    Priority,
+    DueAt,
+    Rank
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum View {
+    Today,
+    Upcoming,
+    Overdue
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMode {
+    Any,
+    All
+}
+
+#[derive(Validate, Deserialize)]
+pub struct QueryDto {
+    #[validate(custom(function = "state_validate"))]
+    pub state: Option<String>,
+
+    #[validate(custom(function = "priority_validate"))]
+    pub priority: Option<String>,
+
+    /// This and the other created and updated bounds take an offset, or
+    /// are read in the user's timezone without one.
+    #[validate(custom(function = "datetime_validate"))]
+    pub created_from: Option<String>,
+
+    #[validate(custom(function = "datetime_validate"))]
+    pub created_to: Option<String>,
+
+    #[validate(custom(function = "datetime_validate"))]
+    pub updated_from: Option<String>,
+
+    #[validate(custom(function = "datetime_validate"))]
+    pub updated_to: Option<String>,
+
+    /// Defaults to `rank`, which is ascending unless `order` says otherwise.
+    pub sort: Option<SortField>,
+
+    pub order: Option<SortOrder>,
+
+    pub view: Option<View>,
+
+    #[validate(range(min=1, message="min=1"))]
+    pub project_id: Option<i32>,
+
+    pub inbox: Option<bool>,
+
+    /// Comma separated tag ids, matched according to `tag_mode`.
+    #[validate(length(min=1, max=512, message="min=1, max=512"))]
+    pub tags: Option<String>,
+
+    pub tag_mode: Option<TagMode>,
+
+    #[validate(length(min=1, max=1024, message="min=1, max=1024"))]
+    pub cursor: Option<String>,
+
+    #[validate(range(min=1, max=100, message="min=1, max=100"))]
+    pub limit: Option<i64>,
+
+    /// Adds `body_html` to every task of the page.
+    pub html: Option<bool>
 }
 
-fn state_validate(state: &str) -> Result<(), ValidationError> {
-    if  state != "TO_DO"       &&
-        state != "IN_PROGRESS" &&
-        state != "DONE" {
+/// Query of a single task read.
+#[derive(Deserialize)]
+pub struct ReadDto {
+    /// Adds `body_html` to the task.
+    pub html: Option<bool>
+}
+
+#[derive(Serialize)]
+pub struct TaskPage {
+    pub data: Vec<Task>,
+    pub next_cursor: Option<String>
+}
+
+impl Task {
+    /// Fills `body_html` from the current body.
+    pub fn render_body(&mut self) {
+        self.body_html = Some(markdown::html(self.body.as_deref().unwrap_or("")));
+    }
+}
+
+/// Only checks the form of a state name. Whether the task's workflow has
+/// the state is checked when the task is written.
+pub fn state_validate(state: &str) -> Result<(), ValidationError> {
+    let pattren = Regex::new(r"^[A-Za-z0-9_-]+( [A-Za-z0-9_-]+)*$").unwrap();
+    if state.len() > 32 || !pattren.is_match(state) {
         return Err(
             ValidationError::new(
-                "state must be one of ('TO_DO', 'IN_PROGRESS', 'DONE')"
+                "state must be letters, digits, '_' or '-' separated by single spaces, max=32"
             )
         );
     }
     return Ok(());
 }
 
-fn priority_validate(priority: &str) -> Result<(), ValidationError> {
+pub fn priority_validate(priority: &str) -> Result<(), ValidationError> {
     if  priority != "LOW"    &&
         priority != "MEDIUM" &&
         priority != "HIGH" {
@@ -86,4 +386,44 @@ fn priority_validate(priority: &str) -> Result<(), ValidationError> {
         );
     }
     return Ok(());
+}
+
+pub fn datetime_validate(datetime: &str) -> Result<(), ValidationError> {
+    let pattren = Regex::new(
+        r"^\d{4}-\d{2}-\d{2}(T\d{2}:\d{2}(:\d{2}(\.\d{1,6})?)?(Z|[+-]\d{2}(:?\d{2})?)?)?$"
+    ).unwrap();
+    if !pattren.is_match(datetime) {
+        return Err(
+            ValidationError::new(
+                "datetime must be 'YYYY-MM-DD' or 'YYYY-MM-DDTHH:MM:SS[Z|+HH:MM]'"
+            )
+        );
+    }
+    return Ok(());
+}
+
+/// Tells a field sent as `null`, `Some(None)`, from a missing one, `None`.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>
+{
+    return Option::<T>::deserialize(deserializer).map(Some);
+}
+
+fn bulk_validate(bulk_dto: &BulkDto) -> Result<(), ValidationError> {
+    if bulk_dto.action == BulkAction::SetState && bulk_dto.state.is_none() {
+        return Err(ValidationError::new("state is required for set_state"));
+    }
+    if bulk_dto.action == BulkAction::SetPriority && bulk_dto.priority.is_none() {
+        return Err(ValidationError::new("priority is required for set_priority"));
+    }
+    return Ok(());
+}
+
+fn reorder_validate(reorder_dto: &ReorderDto) -> Result<(), ValidationError> {
+    if reorder_dto.after_id.is_none() && reorder_dto.before_id.is_none() {
+        return Err(ValidationError::new("after_id or before_id is required"));
+    }
+    return Ok(());
 }
\ No newline at end of file
@@ -0,0 +1,107 @@
+use serde::{
+    Deserialize,
+    Serialize
+};
+use sqlx::types::Json;
+use validator::{
+    Validate,
+    ValidationError
+};
+
+use crate::modules::task::{
+    priority_validate,
+    TaskTag
+};
+
+
+/// A saved starting point for tasks, instantiated into a new task with its
+/// tags and checklist in one call.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Template {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub title: String,
+    pub body: String,
+    pub priority: String,
+    pub tags: Json<Vec<TaskTag>>,
+    /// Titles of the checklist items the task starts with.
+    pub checklist: Vec<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>
+}
+
+#[derive(Validate, Deserialize)]
+pub struct CreateDto {
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub name: String,
+
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub title: String,
+
+    #[validate(length(min=1, max=6000, message="min=1, max=6000"))]
+    pub body: Option<String>,
+
+    #[validate(
+        length(min=3, max=6, message="min=3, max=6"),
+        custom(function = "priority_validate")
+    )]
+    pub priority: Option<String>,
+
+    #[validate(length(max=50, message="max=50"))]
+    pub tag_ids: Option<Vec<i32>>,
+
+    #[validate(
+        length(max=100, message="max=100"),
+        custom(function = "checklist_validate")
+    )]
+    pub checklist: Option<Vec<String>>
+}
+
+/// Fields left out are kept. `tag_ids` and `checklist` replace the whole
+/// list, and an empty `body` clears it.
+#[derive(Validate, Deserialize)]
+pub struct UpdateDto {
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub name: Option<String>,
+
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub title: Option<String>,
+
+    #[validate(length(max=6000, message="max=6000"))]
+    pub body: Option<String>,
+
+    #[validate(
+        length(min=3, max=6, message="min=3, max=6"),
+        custom(function = "priority_validate")
+    )]
+    pub priority: Option<String>,
+
+    #[validate(length(max=50, message="max=50"))]
+    pub tag_ids: Option<Vec<i32>>,
+
+    #[validate(
+        length(max=100, message="max=100"),
+        custom(function = "checklist_validate")
+    )]
+    pub checklist: Option<Vec<String>>
+}
+
+/// What the new task takes besides the template. The fields are checked
+/// by the rules of `task::CreateDto`, which the task is created through.
+#[derive(Deserialize)]
+pub struct InstantiateDto {
+    /// Defaults to the title of the template.
+    pub title: Option<String>,
+    pub state: Option<String>,
+    pub start_at: Option<String>,
+    pub due_at: Option<String>,
+    pub project_id: Option<i32>
+}
+
+fn checklist_validate(checklist: &[String]) -> Result<(), ValidationError> {
+    if checklist.iter().any(|title| title.is_empty() || title.chars().count() > 255) {
+        return Err(ValidationError::new("checklist titles must be min=1, max=255"));
+    }
+    return Ok(());
+}
\ No newline at end of file
@@ -0,0 +1,118 @@
+use serde::{
+    Deserialize,
+    Serialize
+};
+use validator::Validate;
+
+use crate::modules::{
+    stats::date_validate,
+    task::datetime_validate
+};
+
+
+/// Time a user spent on a task. A running timer has no `ended_at` and
+/// counts up to now.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct TimeEntry {
+    pub id: i32,
+    pub task_id: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub seconds: i64,
+    pub note: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>
+}
+
+/// The entries of every user on a task, and the time they add up to.
+#[derive(Serialize)]
+pub struct TaskTime {
+    pub total_seconds: i64,
+    pub entries: Vec<TimeEntry>
+}
+
+#[derive(Validate, Deserialize)]
+pub struct StartDto {
+    #[validate(length(max=255, message="max=255"))]
+    pub note: Option<String>
+}
+
+/// Naive times are read in the user's timezone.
+#[derive(Validate, Deserialize)]
+pub struct CreateDto {
+    #[validate(custom(function = "datetime_validate"))]
+    pub started_at: String,
+
+    #[validate(custom(function = "datetime_validate"))]
+    pub ended_at: String,
+
+    #[validate(length(max=255, message="max=255"))]
+    pub note: Option<String>
+}
+
+/// Setting `ended_at` on a running timer stops it.
+#[derive(Validate, Deserialize)]
+pub struct UpdateDto {
+    #[validate(custom(function = "datetime_validate"))]
+    pub started_at: Option<String>,
+
+    #[validate(custom(function = "datetime_validate"))]
+    pub ended_at: Option<String>,
+
+    #[validate(length(max=255, message="max=255"))]
+    pub note: Option<String>
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Group {
+    Project,
+    Tag
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Json,
+    Csv
+}
+
+#[derive(Validate, Deserialize)]
+pub struct ReportQuery {
+    /// First day in the user's timezone, 29 days before `to` by default.
+    #[validate(custom(function = "date_validate"))]
+    pub from: Option<String>,
+
+    /// Last day, today by default.
+    #[validate(custom(function = "date_validate"))]
+    pub to: Option<String>,
+
+    /// Defaults to `project`.
+    pub group: Option<Group>,
+
+    pub format: Option<ReportFormat>
+}
+
+/// Time logged on `date` under one project or tag. Tasks outside any
+/// project, or without tags, are reported with a null `id` and `name`.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct ReportRow {
+    pub date: String,
+    pub id: Option<i32>,
+    pub name: Option<String>,
+    pub seconds: i64
+}
+
+/// The user's own time, by the day an entry started on in their timezone.
+/// A task with several tags counts under each of them, so the rows of a
+/// tag report can add up to more than `total_seconds`.
+#[derive(Serialize)]
+pub struct Report {
+    pub from: String,
+    pub to: String,
+    pub group: Group,
+    pub total_seconds: i64,
+    pub rows: Vec<ReportRow>
+}
\ No newline at end of file
@@ -0,0 +1,92 @@
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+/// Rows accepted by one import request.
+pub const MAX_IMPORT_ROWS: usize = 1000;
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Json,
+    Csv,
+    TodoTxt
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<Format>
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    pub format: Option<Format>,
+
+    /// Runs every row and reports the outcome, then rolls everything back.
+    pub dry_run: Option<bool>
+}
+
+/// One exported task. Its fields are the ones `task::CreateDto` reads,
+/// so an export can be imported again as-is.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct ExportRow {
+    pub id: i32,
+    pub title: String,
+    pub body: String,
+    pub state: String,
+    pub priority: String,
+    pub start_at: Option<String>,
+    pub due_at: Option<String>,
+    pub project_id: Option<i32>,
+    pub tag_ids: Vec<i32>,
+    pub created_at: String
+}
+
+/// One CSV row. `tag_ids` holds the ids separated by `;`.
+#[derive(Deserialize)]
+pub struct CsvRow {
+    pub title: String,
+    pub body: Option<String>,
+    pub state: Option<String>,
+    pub priority: Option<String>,
+    pub start_at: Option<String>,
+    pub due_at: Option<String>,
+    pub project_id: Option<i32>,
+    pub tag_ids: Option<String>
+}
+
+/// One exported task in the todo.txt format. Dates are `YYYY-MM-DD` in the
+/// user's timezone, and `project` and `tags` hold names.
+#[derive(sqlx::FromRow)]
+pub struct TodoTxtRow {
+    pub id: i32,
+    pub title: String,
+    pub closed: bool,
+    pub priority: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub due_date: Option<String>,
+    pub start_date: Option<String>,
+    pub created_date: String,
+    /// When the task was closed, only used for closed tasks.
+    pub completed_date: String
+}
+
+#[derive(Serialize)]
+pub struct ImportResult {
+    /// 1-based position of the row in the file, not counting the CSV header.
+    /// For todo.txt, the line number.
+    pub row: usize,
+    pub status: u16,
+    pub task_id: Option<i32>,
+    pub error: Option<String>
+}
+
+#[derive(Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<ImportResult>
+}
\ No newline at end of file
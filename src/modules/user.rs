@@ -8,6 +8,7 @@ pub struct User {
     pub name: String,
     pub username: String,
     pub email: String,
+    pub timezone: String,
     #[serde(skip)]
     pub password: String,
     pub create_at: Option<String>,
@@ -56,7 +57,13 @@ pub struct UpdateInformationDto {
         length(min=5, max=255, message="min=2 && max=255"),
         email
     )]
-    pub email: Option<String>
+    pub email: Option<String>,
+
+    #[validate(
+        length(min=1, max=64, message="min=1 && max=64"),
+        custom(function = "timezone_validate")
+    )]
+    pub timezone: Option<String>
 }
 
 #[derive(Validate, Deserialize)]
@@ -109,3 +116,12 @@ fn password_validate(password: &str) -> Result<(), ValidationError> {
         }
     Ok(())
 }
+
+
+fn timezone_validate(timezone: &str) -> Result<(), ValidationError> {
+    let pattren = Regex::new(r"^[A-Za-z_]+(/[A-Za-z0-9_+\-]+)*$").unwrap();
+    if !pattren.is_match(timezone) {
+        return Err(ValidationError::new("timezone must be an IANA name like 'Europe/Berlin'"));
+    }
+    Ok(())
+}
\ No newline at end of file
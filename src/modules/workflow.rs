@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+use sqlx::types::Json;
+use validator::{
+    Validate,
+    ValidationError
+};
+
+use crate::modules::task::state_validate;
+
+
+#[derive(Serialize, Deserialize, sqlx::FromRow, Clone)]
+pub struct WorkflowState {
+    pub id: i32,
+    pub name: String,
+    pub position: i32,
+    pub closed: bool
+}
+
+/// Ordered states a task goes through. Tasks start in the first state and
+/// are finished in any `closed` one.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Workflow {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    /// Used by the inbox and by projects without a workflow of their own.
+    pub is_default: bool,
+    pub states: Json<Vec<WorkflowState>>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>
+}
+
+/// Where a state stands in its workflow, for formats that only know a
+/// fixed set of states, like the iCalendar `STATUS`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum StateKind {
+    /// The first state.
+    Initial,
+    /// Any later state that is not closed.
+    Active,
+    Closed
+}
+
+/// One state of the list sent to create or update a workflow. Listing the
+/// `id` of an existing state keeps it, and its tasks, under the new name.
+#[derive(Validate, Serialize, Deserialize)]
+pub struct StateDto {
+    #[validate(range(min=1, message="min=1"))]
+    pub id: Option<i32>,
+
+    #[validate(
+        length(min=1, max=32, message="min=1, max=32"),
+        custom(function = "state_validate")
+    )]
+    pub name: String,
+
+    pub closed: Option<bool>
+}
+
+#[derive(Validate, Deserialize)]
+#[validate(schema(function = "create_validate"))]
+pub struct CreateDto {
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub name: String,
+
+    #[validate(
+        length(min=2, max=20, message="min=2, max=20"),
+        nested
+    )]
+    pub states: Vec<StateDto>
+}
+
+/// `states` replaces the whole list, in order. Tasks of a state left out
+/// move to the first remaining state that is as closed as theirs was.
+#[derive(Validate, Deserialize)]
+#[validate(schema(function = "update_validate"))]
+pub struct UpdateDto {
+    #[validate(length(min=1, max=255, message="min=1, max=255"))]
+    pub name: Option<String>,
+
+    #[validate(
+        length(min=2, max=20, message="min=2, max=20"),
+        nested
+    )]
+    pub states: Option<Vec<StateDto>>
+}
+
+/// Gives a project its own workflow, or back the owner's default one when
+/// `workflow_id` is null.
+#[derive(Validate, Deserialize)]
+pub struct SetDto {
+    #[validate(range(min=1, message="min=1"))]
+    pub workflow_id: Option<i32>
+}
+
+fn create_validate(create_dto: &CreateDto) -> Result<(), ValidationError> {
+    return states_validate(&create_dto.states);
+}
+
+fn update_validate(update_dto: &UpdateDto) -> Result<(), ValidationError> {
+    match &update_dto.states {
+        Some(states) => return states_validate(states),
+        None => return Ok(())
+    }
+}
+
+fn states_validate(states: &[StateDto]) -> Result<(), ValidationError> {
+    if states.first().is_some_and(|state| state.closed == Some(true)) {
+        return Err(ValidationError::new("the first state must NOT be closed"));
+    }
+    if !states.iter().any(|state| state.closed == Some(true)) {
+        return Err(ValidationError::new("at least one state must be closed"));
+    }
+    let mut names = HashSet::new();
+    if !states.iter().all(|state| names.insert(state.name.as_str())) {
+        return Err(ValidationError::new("state names must be unique"));
+    }
+    let mut ids = HashSet::new();
+    if !states.iter().filter_map(|state| state.id).all(|id| ids.insert(id)) {
+        return Err(ValidationError::new("state ids must be unique"));
+    }
+    return Ok(());
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use axum::{
+    middleware, 
+    routing::{
+        any,
+        delete,
+        post
+    }, 
+    Router
+};
+
+use crate::{
+    middlewares,
+    handlers
+};
+
+/// CalDAV uses its own methods (PROPFIND, REPORT), so every path takes any
+/// method and the handler dispatches on it. Collections answer with and
+/// without the trailing slash.
+pub fn main() -> Router {
+    Router::new()
+        .route("/dav", any(handlers::caldav::root))
+        .route("/dav/", any(handlers::caldav::root))
+        .route("/dav/principals/{username}", any(handlers::caldav::principal))
+        .route("/dav/principals/{username}/", any(handlers::caldav::principal))
+        .route("/dav/calendars/{username}", any(handlers::caldav::home))
+        .route("/dav/calendars/{username}/", any(handlers::caldav::home))
+        .route("/dav/calendars/{username}/tasks", any(handlers::caldav::calendar))
+        .route("/dav/calendars/{username}/tasks/", any(handlers::caldav::calendar))
+        .route("/dav/calendars/{username}/tasks/{name}", any(handlers::caldav::object))
+        .route_layer(middleware::from_fn(middlewares::auth::basic_guard))
+}
+
+/// The CalDAV token is managed with the session like any other resource.
+pub fn token() -> Router {
+    Router::new()
+        .route("/token", post(handlers::caldav::create_token))
+        .route("/token", delete(handlers::caldav::delete_token))
+        .route_layer(middleware::from_fn(middlewares::auth::auth_guard))
+}
\ No newline at end of file
@@ -0,0 +1,22 @@
+use axum::{
+    middleware, 
+    routing::{
+        delete, 
+        get, 
+        post
+    }, 
+    Router
+};
+
+use crate::{
+    middlewares,
+    handlers
+};
+
+pub fn main() -> Router {
+    Router::new()
+        .route("/token", post(handlers::feed::create_token))
+        .route("/token", delete(handlers::feed::delete_token))
+        .route_layer(middleware::from_fn(middlewares::auth::auth_guard))
+        .route("/{file_name}", get(handlers::feed::calendar))
+}
\ No newline at end of file
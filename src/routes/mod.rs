@@ -2,9 +2,31 @@ use axum::Router;
 
 mod user;
 mod task;
+mod project;
+mod tag;
+mod share;
+mod feed;
+mod caldav;
+mod workflow;
+mod time;
+mod template;
 
 pub fn main() -> Router {
     Router::new()
         .nest("/user", user::main())
         .nest("/task", task::main())
+        .nest("/project", project::main())
+        .nest("/tag", tag::main())
+        .nest("/share", share::main())
+        .nest("/feed", feed::main())
+        .nest("/caldav", caldav::token())
+        .nest("/workflow", workflow::main())
+        .nest("/time", time::main())
+        .nest("/template", template::main())
+}
+
+/// Served without CORS: CalDAV clients are not browsers, and their `OPTIONS`
+/// requests are not preflights.
+pub fn dav() -> Router {
+    caldav::main()
 }
\ No newline at end of file
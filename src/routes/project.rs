@@ -0,0 +1,26 @@
+use axum::{
+    middleware, 
+    routing::{
+        delete, 
+        get, 
+        patch, 
+        post
+    }, 
+    Router
+};
+
+use crate::{
+    middlewares,
+    handlers
+};
+
+pub fn main() -> Router {
+    Router::new()
+        .route("/", get(handlers::project::get_all))
+        .route("/create", post(handlers::project::create))
+        .route("/update/{id}", patch(handlers::project::update))
+        .route("/delete/{id}", delete(handlers::project::delete))
+        .route("/{id}/workflow", get(handlers::workflow::get_for_project))
+        .route("/{id}/workflow", patch(handlers::workflow::set_for_project))
+        .route_layer(middleware::from_fn(middlewares::auth::auth_guard))
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use axum::{
+    middleware, 
+    routing::{
+        delete, 
+        get, 
+        patch, 
+        post
+    }, 
+    Router
+};
+
+use crate::{
+    middlewares,
+    handlers
+};
+
+pub fn main() -> Router {
+    Router::new()
+        .route("/invitations", get(handlers::share::get_invitations))
+        .route("/accept/{id}", post(handlers::share::accept))
+        .route("/decline/{id}", post(handlers::share::decline))
+        .route("/task/{id}", get(handlers::share::get_task_members))
+        .route("/task/{id}", post(handlers::share::invite_task))
+        .route("/project/{id}", get(handlers::share::get_project_members))
+        .route("/project/{id}", post(handlers::share::invite_project))
+        .route("/update/{id}", patch(handlers::share::update))
+        .route("/delete/{id}", delete(handlers::share::delete))
+        .route_layer(middleware::from_fn(middlewares::auth::auth_guard))
+}
\ No newline at end of file
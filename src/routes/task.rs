@@ -1,4 +1,5 @@
 use axum::{
+    extract::DefaultBodyLimit,
     middleware, 
     routing::{
         delete, 
@@ -11,14 +12,57 @@ use axum::{
 
 use crate::{
     middlewares,
-    handlers
+    handlers,
+    modules::attachment::MAX_ATTACHMENT_SIZE
 };
 
 pub fn main() -> Router {
     Router::new()
         .route("/", get(handlers::task::get_all))
+        .route("/search", get(handlers::task::search))
         .route("/create", post(handlers::task::create))
+        .route("/clone/{id}", post(handlers::task::clone))
         .route("/update/{id}", patch(handlers::task::update))
+        .route("/move/{id}", patch(handlers::task::move_to_project))
+        .route("/reorder/{id}", patch(handlers::task::reorder))
+        .route("/bulk", post(handlers::task::bulk))
+        .route("/stats", get(handlers::stats::get))
+        .route("/export", get(handlers::transfer::export))
+        .route("/import", post(handlers::transfer::import))
         .route("/delete/{id}", delete(handlers::task::delete))
+        .route("/{id}", get(handlers::task::get_one))
+        .route("/trash", get(handlers::trash::get_all))
+        .route("/trash/restore/{id}", patch(handlers::trash::restore))
+        .route("/trash/empty", delete(handlers::trash::empty))
+        .route("/{id}/recurrence", patch(handlers::recurrence::set))
+        .route("/{id}/recurrence", delete(handlers::recurrence::stop))
+        .route("/{id}/checklist", get(handlers::checklist::get_all))
+        .route("/{id}/checklist/create", post(handlers::checklist::create))
+        .route("/{id}/checklist/update/{item_id}", patch(handlers::checklist::update))
+        .route("/{id}/checklist/delete/{item_id}", delete(handlers::checklist::delete))
+        .route("/{id}/checkboxes", get(handlers::checkbox::get_all))
+        .route("/{id}/checkboxes/{index}", patch(handlers::checkbox::toggle))
+        .route("/{id}/comments", get(handlers::comment::get_all))
+        .route("/{id}/comments/create", post(handlers::comment::create))
+        .route("/{id}/comments/update/{comment_id}", patch(handlers::comment::update))
+        .route("/{id}/comments/delete/{comment_id}", delete(handlers::comment::delete))
+        .route("/{id}/history", get(handlers::history::get_all))
+        .route("/{id}/time", get(handlers::time_entry::get_all))
+        .route("/{id}/time/start", post(handlers::time_entry::start))
+        .route("/{id}/time/stop", post(handlers::time_entry::stop))
+        .route("/{id}/time/create", post(handlers::time_entry::create))
+        .route("/{id}/time/update/{entry_id}", patch(handlers::time_entry::update))
+        .route("/{id}/time/delete/{entry_id}", delete(handlers::time_entry::delete))
+        .route("/{id}/dependencies", get(handlers::dependency::get_all))
+        .route("/{id}/dependencies/create", post(handlers::dependency::create))
+        .route("/{id}/dependencies/delete/{blocked_by_id}", delete(handlers::dependency::delete))
+        .route("/{id}/attachments", get(handlers::attachment::get_all))
+        .route(
+            "/{id}/attachments/create",
+            post(handlers::attachment::create)
+                .layer(DefaultBodyLimit::max(MAX_ATTACHMENT_SIZE))
+        )
+        .route("/{id}/attachments/{attachment_id}", get(handlers::attachment::download))
+        .route("/{id}/attachments/delete/{attachment_id}", delete(handlers::attachment::delete))
         .route_layer(middleware::from_fn(middlewares::auth::auth_guard))
 }
\ No newline at end of file
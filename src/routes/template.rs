@@ -0,0 +1,26 @@
+use axum::{
+    middleware, 
+    routing::{
+        delete, 
+        get, 
+        patch, 
+        post
+    }, 
+    Router
+};
+
+use crate::{
+    middlewares,
+    handlers
+};
+
+pub fn main() -> Router {
+    Router::new()
+        .route("/", get(handlers::template::get_all))
+        .route("/create", post(handlers::template::create))
+        .route("/update/{id}", patch(handlers::template::update))
+        .route("/delete/{id}", delete(handlers::template::delete))
+        .route("/{id}", get(handlers::template::get_one))
+        .route("/{id}/instantiate", post(handlers::template::instantiate))
+        .route_layer(middleware::from_fn(middlewares::auth::auth_guard))
+}
\ No newline at end of file
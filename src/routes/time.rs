@@ -0,0 +1,17 @@
+use axum::{
+    middleware, 
+    routing::get, 
+    Router
+};
+
+use crate::{
+    middlewares,
+    handlers
+};
+
+pub fn main() -> Router {
+    Router::new()
+        .route("/running", get(handlers::time_entry::running))
+        .route("/report", get(handlers::time_entry::report))
+        .route_layer(middleware::from_fn(middlewares::auth::auth_guard))
+}
\ No newline at end of file
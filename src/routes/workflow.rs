@@ -0,0 +1,24 @@
+use axum::{
+    middleware, 
+    routing::{
+        delete, 
+        get, 
+        patch, 
+        post
+    }, 
+    Router
+};
+
+use crate::{
+    middlewares,
+    handlers
+};
+
+pub fn main() -> Router {
+    Router::new()
+        .route("/", get(handlers::workflow::get_all))
+        .route("/create", post(handlers::workflow::create))
+        .route("/update/{id}", patch(handlers::workflow::update))
+        .route("/delete/{id}", delete(handlers::workflow::delete))
+        .route_layer(middleware::from_fn(middlewares::auth::auth_guard))
+}
\ No newline at end of file
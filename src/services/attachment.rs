@@ -0,0 +1,246 @@
+use sha2::{
+    Digest,
+    Sha256
+};
+use sqlx::{
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        attachment::{
+            Attachment,
+            UploadDto
+        },
+        share::Rank
+    },
+    services,
+    storage::{
+        get_quota,
+        get_storage,
+        Storage
+    },
+    error::AppError
+};
+
+const ATTACHMENT_COLUMNS: &str = r#"
+    id,
+    task_id,
+    user_id,
+    name,
+    size,
+    mime_type,
+    checksum,
+    to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at
+"#;
+
+/// Stores the file and attaches it to the task. The size counts towards the
+/// uploader's quota, whoever owns the task.
+pub async fn create(
+    upload_dto: UploadDto,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Attachment, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut tx).await?;
+
+    // Locking the user serializes their uploads, so two of them can NOT
+    // both fit in the remaining quota.
+    let used = sqlx::query_scalar::<_, i64>(r#"
+        WITH locked AS (
+            SELECT id FROM users
+            WHERE id = $1
+            FOR UPDATE
+        )
+        SELECT COALESCE(SUM(size), 0)::BIGINT FROM attachments
+        WHERE user_id = (SELECT id FROM locked)
+    "#)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await;
+    let used = match used {
+        Ok(used) => used,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let size = upload_dto.data.len() as i64;
+    if used + size > get_quota() {
+        return Err(AppError::ValidationError(
+            "file: storage quota exceeded".to_string()
+        ));
+    }
+
+    let checksum = format!("{:x}", Sha256::digest(&upload_dto.data));
+    let storage_key = uuid::Uuid::new_v4().simple().to_string();
+    let storage = get_storage();
+    if let Err(e) = storage.put(&storage_key, &upload_dto.data).await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    let result = sqlx::query_as::<_, Attachment>(&format!(r#"
+        INSERT INTO attachments (task_id, user_id, name, size, mime_type, checksum, storage_key)
+        VALUES ( $1, $2, $3, $4, $5, $6, $7 )
+        RETURNING {ATTACHMENT_COLUMNS}
+    "#))
+        .bind(task_id)
+        .bind(user_id)
+        .bind(upload_dto.name)
+        .bind(size)
+        .bind(upload_dto.mime_type)
+        .bind(checksum)
+        .bind(&storage_key)
+        .fetch_one(&mut *tx)
+        .await;
+    let attachment = match result {
+        Ok(attachment) => attachment,
+        Err(e) => {
+            error!("{:#?}", e);
+            remove_blobs(vec![storage_key]).await;
+            return Err(AppError::InternalServer);
+        }
+    };
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        remove_blobs(vec![storage_key]).await;
+        return Err(AppError::InternalServer);
+    }
+    return Ok(attachment);
+}
+
+pub async fn get_all(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Attachment>, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let result = sqlx::query_as::<_, Attachment>(&format!(r#"
+        SELECT {ATTACHMENT_COLUMNS}
+        FROM attachments
+        WHERE task_id = $1
+        ORDER BY id;
+    "#))
+        .bind(task_id)
+        .fetch_all(&mut *conn)
+        .await;
+    match result {
+        Ok(attachments) => return Ok(attachments),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Returns the attachment with its content.
+pub async fn download(
+    id: i32,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(Attachment, Vec<u8>), AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let result = sqlx::query_as::<_, Attachment>(&format!(r#"
+        SELECT {ATTACHMENT_COLUMNS}, storage_key
+        FROM attachments
+        WHERE
+            id      = $1 AND
+            task_id = $2
+    "#))
+        .bind(id)
+        .bind(task_id)
+        .fetch_one(&mut *conn)
+        .await;
+    let attachment = match result {
+        Ok(attachment) => attachment,
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    };
+    match get_storage().get(&attachment.storage_key).await {
+        Ok(data) => return Ok((attachment, data)),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn delete(
+    id: i32,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut conn).await?;
+    let result = sqlx::query_scalar::<_, String>(r#"
+        DELETE FROM attachments
+        WHERE
+            id      = $1 AND
+            task_id = $2
+        RETURNING storage_key
+    "#)
+        .bind(id)
+        .bind(task_id)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(storage_key) => {
+            remove_blobs(vec![storage_key]).await;
+            return Ok(());
+        }
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+/// Removes blobs whose rows are gone. Failures are only logged: a leftover
+/// file wastes space but is never served again.
+pub async fn remove_blobs(storage_keys: Vec<String>) {
+    let storage = get_storage();
+    for storage_key in storage_keys {
+        if let Err(e) = storage.delete(&storage_key).await {
+            error!("{:#?}", e);
+        }
+    }
+}
\ No newline at end of file
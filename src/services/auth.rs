@@ -57,6 +57,7 @@ pub async fn get_user_by_session(
             name, 
             email, 
             username, 
+            timezone,
             password,
             to_char(create_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as create_at, 
             to_char(update_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as update_at
@@ -0,0 +1,458 @@
+use regex::Regex;
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    modules::{
+        caldav::{
+            CaldavToken,
+            VTodo
+        },
+        feed::FeedTask,
+        workflow::StateKind,
+        task::{
+            CreateDto,
+            UpdateDto
+        },
+        user::User
+    },
+    services::{
+        self,
+        feed::{
+            hash_token,
+            CALENDAR_COLUMNS
+        }
+    },
+    ical,
+    error::AppError
+};
+
+/// Creates the user's CalDAV token, replacing and so revoking any older one.
+pub async fn create_token(
+    user: &User,
+    pool: &Pool<Postgres>
+) -> Result<CaldavToken, AppError> {
+    let token = format!(
+        "{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    );
+    let result = sqlx::query(r#"
+        INSERT INTO caldav_tokens (user_id, token_hash)
+        VALUES ( $1, $2 )
+        ON CONFLICT (user_id) DO UPDATE
+        SET
+            token_hash = EXCLUDED.token_hash,
+            created_at = CURRENT_TIMESTAMP
+    "#)
+        .bind(user.id)
+        .bind(hash_token(&token))
+        .execute(pool)
+        .await;
+    match result {
+        Ok(_) => return Ok(CaldavToken {
+            username: user.username.clone(),
+            token,
+            url: "/api/v1/dav/".to_string()
+        }),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn delete_token(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        DELETE FROM caldav_tokens
+        WHERE user_id = $1
+    "#)
+        .bind(user_id)
+        .execute(pool)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// The active user owning both `username` and the CalDAV `token`.
+pub async fn get_user_by_token(
+    username: &str,
+    token: &str,
+    pool: &Pool<Postgres>
+) -> Result<User, AppError> {
+    let user = sqlx::query_as::<_, User>(r#"
+        SELECT
+            users.id,
+            users.name,
+            users.email,
+            users.username,
+            users.timezone,
+            users.password,
+            to_char(users.create_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as create_at,
+            to_char(users.update_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as update_at
+        FROM users
+        JOIN caldav_tokens ON caldav_tokens.user_id = users.id
+        WHERE
+            caldav_tokens.token_hash = $1 AND
+            users.username           = $2 AND
+            users.state              = 'active'
+    "#)
+        .bind(hash_token(token))
+        .bind(username)
+        .fetch_one(pool)
+        .await;
+    match user {
+        Ok(data) => return Ok(data),
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::Unauthorized),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Every live task the user can see, as objects of their `tasks` collection.
+pub async fn get_all(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<FeedTask>, AppError> {
+    let result = sqlx::query_as::<_, FeedTask>(&format!(r#"
+        SELECT {CALENDAR_COLUMNS}
+        FROM tasks
+        LEFT JOIN caldav_objects ON caldav_objects.task_id = tasks.id
+        WHERE tasks.id IN (SELECT task_id FROM task_access WHERE user_id = $1)
+        ORDER BY tasks.rank, tasks.id
+    "#))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(tasks) => return Ok(tasks),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn get_one(
+    name: &str,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<FeedTask, AppError> {
+    let result = sqlx::query_as::<_, FeedTask>(&format!(r#"
+        SELECT {CALENDAR_COLUMNS}
+        FROM tasks
+        LEFT JOIN caldav_objects ON caldav_objects.task_id = tasks.id
+        WHERE
+            tasks.id IN (SELECT task_id FROM task_access WHERE user_id = $1) AND
+            COALESCE(caldav_objects.name, 'task-' || tasks.id || '.ics') = $2
+    "#))
+        .bind(user_id)
+        .bind(name)
+        .fetch_one(pool)
+        .await;
+    match result {
+        Ok(task) => return Ok(task),
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::NotFoundData),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Changes whenever an object of the collection is added, changed or
+/// removed, so clients can skip syncing an unchanged collection.
+pub async fn ctag(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<String, AppError> {
+    let result = sqlx::query_scalar::<_, String>(r#"
+        SELECT md5(COALESCE(string_agg(id || ':' || version, ',' ORDER BY id), ''))
+        FROM tasks
+        WHERE id IN (SELECT task_id FROM task_access WHERE user_id = $1)
+    "#)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await;
+    match result {
+        Ok(ctag) => return Ok(ctag),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Stores the VTODO in `data` under `name`: updates the task already served
+/// there, or creates one. Returns whether a task was created.
+///
+/// A PUT replaces the whole object, so a description or date missing from
+/// `data` is cleared on the task.
+pub async fn put(
+    name: &str,
+    data: &str,
+    version: Option<Vec<i32>>,
+    create_only: bool,
+    user: &User,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let vtodo = parse(data)?;
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user.id, &mut tx).await?;
+    let timezone = timezone(vtodo.tzid.as_deref(), &user.timezone, &mut tx).await?;
+    let created = match find_id(name, user.id, &mut tx).await? {
+        Some(id) => {
+            if create_only {
+                return Err(AppError::PreconditionFailed);
+            }
+            // A task keeps its state while the client's STATUS still maps
+            // onto it, so a CalDAV edit does not undo a finer workflow state.
+            let task = services::task::find(id, user.id, &mut tx).await?;
+            let states = services::workflow::states_for(task.user_id, task.project_id, &mut tx).await?;
+            let state = if services::workflow::kind(&states, &task.state) == vtodo.state {
+                None
+            } else {
+                services::workflow::pick(&states, vtodo.state).map(|state| state.name.clone())
+            };
+            let clear_body = vtodo.body.is_none();
+            let update_dto = UpdateDto {
+                title: Some(vtodo.title),
+                body: vtodo.body,
+                state,
+                priority: Some(vtodo.priority),
+                start_at: Some(vtodo.start_at),
+                due_at: Some(vtodo.due_at),
+                ..Default::default()
+            };
+            if let Err(e) = update_dto.validate() {
+                return Err(AppError::ValidationError(e.to_string()));
+            }
+            services::task::apply_update(update_dto, id, user.id, version, &timezone, &mut tx).await?;
+            // An empty body is not a valid update, so a missing DESCRIPTION
+            // is cleared on its own.
+            if clear_body {
+                let cleared = sqlx::query(r#"
+                    UPDATE tasks
+                    SET body = ''
+                    WHERE
+                        id    = $1 AND
+                        body <> ''
+                "#)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await;
+                if let Err(e) = cleared {
+                    error!("{:#?}", e);
+                    return Err(AppError::InternalServer);
+                }
+            }
+            false
+        }
+        None => {
+            if version.is_some() {
+                return Err(AppError::PreconditionFailed);
+            }
+            // Names of tasks created through the API are reserved.
+            if Regex::new(r"^task-\d+\.ics$").unwrap().is_match(name) {
+                return Err(AppError::Forbidden);
+            }
+            let states = services::workflow::states_for(user.id, None, &mut tx).await?;
+            let create_dto = CreateDto {
+                title: vtodo.title,
+                body: vtodo.body,
+                state: services::workflow::pick(&states, vtodo.state).map(|state| state.name.clone()),
+                priority: Some(vtodo.priority),
+                start_at: vtodo.start_at,
+                due_at: vtodo.due_at,
+                project_id: None,
+                tag_ids: None,
+                recurrence: None
+            };
+            if let Err(e) = create_dto.validate() {
+                return Err(AppError::ValidationError(e.to_string()));
+            }
+            let task = services::task::apply_create(create_dto, user.id, &timezone, &mut tx).await?;
+            // A trashed task keeps its name for a restore, until the client
+            // reuses the name or the UID.
+            let released = sqlx::query(r#"
+                DELETE FROM caldav_objects
+                WHERE
+                    (name = $1 OR uid = $2) AND
+                    task_id IN (
+                        SELECT task_id FROM trash_access
+                        WHERE
+                            user_id = $3 AND
+                            rank    = 3
+                    )
+            "#)
+                .bind(name)
+                .bind(&vtodo.uid)
+                .bind(user.id)
+                .execute(&mut *tx)
+                .await;
+            if let Err(e) = released {
+                error!("{:#?}", e);
+                return Err(AppError::InternalServer);
+            }
+            let inserted = sqlx::query(r#"
+                INSERT INTO caldav_objects (task_id, name, uid)
+                VALUES ( $1, $2, $3 )
+            "#)
+                .bind(task.id)
+                .bind(name)
+                .bind(&vtodo.uid)
+                .execute(&mut *tx)
+                .await;
+            if let Err(e) = inserted {
+                if let sqlx::Error::Database(db_err) = &e {
+                    if db_err.code().as_deref() == Some("23505") {
+                        return Err(AppError::ValidationError(
+                            "UID: already used by another task".to_string()
+                        ));
+                    }
+                }
+                error!("{:#?}", e);
+                return Err(AppError::InternalServer);
+            }
+            true
+        }
+    };
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(created);
+}
+
+/// Moves the task served under `name` to the trash.
+pub async fn delete(
+    name: &str,
+    version: Option<Vec<i32>>,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let task = get_one(name, user_id, pool).await?;
+    return services::task::delete(task.id, user_id, version, pool).await;
+}
+
+async fn find_id(
+    name: &str,
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<Option<i32>, AppError> {
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT tasks.id
+        FROM tasks
+        LEFT JOIN caldav_objects ON caldav_objects.task_id = tasks.id
+        WHERE
+            tasks.id IN (SELECT task_id FROM task_access WHERE user_id = $1) AND
+            COALESCE(caldav_objects.name, 'task-' || tasks.id || '.ics') = $2
+    "#)
+        .bind(user_id)
+        .bind(name)
+        .fetch_optional(conn)
+        .await;
+    match result {
+        Ok(id) => return Ok(id),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// The zone floating times of the VTODO are read in: its `TZID` when
+/// Postgres knows it, the user's timezone otherwise.
+async fn timezone(
+    tzid: Option<&str>,
+    fallback: &str,
+    conn: &mut PgConnection
+) -> Result<String, AppError> {
+    let Some(tzid) = tzid else {
+        return Ok(fallback.to_string());
+    };
+    let result = sqlx::query_scalar::<_, bool>(r#"
+        SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)
+    "#)
+        .bind(tzid)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(true) => return Ok(tzid.to_string()),
+        Ok(false) => return Ok(fallback.to_string()),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+fn parse(data: &str) -> Result<VTodo, AppError> {
+    let Some(properties) = ical::parse_component(data, "VTODO") else {
+        return Err(AppError::ValidationError("VTODO: component NOT found".to_string()));
+    };
+    let get = |name: &str| properties.iter().find(|property| property.name == name);
+    let text = |name: &str| get(name)
+        .map(|property| ical::unescape_text(&property.value))
+        .filter(|value| !value.trim().is_empty());
+    let date_time = |name: &str| -> Result<Option<String>, AppError> {
+        match get(name) {
+            Some(property) => match ical::date_time(&property.value) {
+                Some(value) => return Ok(Some(value)),
+                None => return Err(AppError::ValidationError(format!("{}: invalid date", name)))
+            },
+            None => return Ok(None)
+        }
+    };
+    let Some(uid) = text("UID") else {
+        return Err(AppError::ValidationError("UID: is required".to_string()));
+    };
+    // Some clients only set COMPLETED or PERCENT-COMPLETE when ticking a to-do.
+    let completed = get("COMPLETED").is_some() ||
+        get("PERCENT-COMPLETE").is_some_and(|property| property.value.trim() == "100");
+    let state = if completed {
+        StateKind::Closed
+    } else {
+        ical::state(get("STATUS").map(|property| property.value.trim()).unwrap_or(""))
+    };
+    let tzid = get("DUE").or(get("DTSTART"))
+        .and_then(|property| property.param("TZID"))
+        .map(|tzid| tzid.to_string());
+    return Ok(VTodo {
+        uid,
+        title: text("SUMMARY").unwrap_or_default(),
+        body: text("DESCRIPTION"),
+        state,
+        priority: ical::task_priority(get("PRIORITY").map(|property| property.value.as_str()).unwrap_or("0")).to_string(),
+        start_at: date_time("DTSTART")?,
+        due_at: date_time("DUE")?,
+        tzid
+    });
+}
\ No newline at end of file
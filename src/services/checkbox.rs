@@ -0,0 +1,91 @@
+use sqlx::{
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        checkbox::{
+            Checkbox,
+            ToggleDto
+        },
+        share::Rank,
+        task::{
+            Task,
+            UpdateDto
+        }
+    },
+    services,
+    markdown,
+    error::AppError
+};
+
+pub async fn get_all(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Checkbox>, AppError> {
+    let task = services::task::get_one(task_id, user_id, pool).await?;
+    return Ok(markdown::checkboxes(task.body.as_deref().unwrap_or("")));
+}
+
+/// Ticks or unticks checkbox `index` of the task body. Only its marker is
+/// rewritten, so concurrent edits to other lines are not lost.
+pub async fn toggle(
+    toggle_dto: ToggleDto,
+    index: usize,
+    task_id: i32,
+    user_id: i32,
+    version: Option<Vec<i32>>,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<Task, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut tx).await?;
+    let result = sqlx::query_as::<_, (Option<String>, i32)>(r#"
+        SELECT body, version FROM tasks
+        WHERE id = $1
+        FOR UPDATE
+    "#)
+        .bind(task_id)
+        .fetch_one(&mut *tx)
+        .await;
+    let (body, stored) = match result {
+        Ok(row) => row,
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::NotFoundData),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    if version.as_ref().is_some_and(|versions| !versions.contains(&stored)) {
+        return Err(AppError::PreconditionFailed);
+    }
+    let body = body.unwrap_or_default();
+    let Some(checkbox) = markdown::checkboxes(&body).into_iter().nth(index) else {
+        return Err(AppError::NotFoundData);
+    };
+    let checked = toggle_dto.checked.unwrap_or(!checkbox.checked);
+    let task = if checked == checkbox.checked {
+        services::task::find(task_id, user_id, &mut tx).await?
+    } else {
+        let update_dto = UpdateDto {
+            body: markdown::toggle(&body, index, checked),
+            ..Default::default()
+        };
+        services::task::apply_update(update_dto, task_id, user_id, version, timezone, &mut tx).await?
+    };
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
+}
\ No newline at end of file
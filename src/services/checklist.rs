@@ -0,0 +1,239 @@
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::checklist::{
+        ChecklistItem,
+        CreateDto,
+        UpdateDto
+    },
+    modules::share::Rank,
+    services,
+    error::AppError
+};
+
+pub async fn create(
+    create_dto: CreateDto,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<ChecklistItem, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut conn).await?;
+    let result = sqlx::query_as::<_, ChecklistItem>(r#"
+        INSERT INTO checklist_items (task_id, title, done, position)
+        SELECT
+            tasks.id,
+            $2,
+            $3,
+            COALESCE((
+                SELECT MAX(position) + 1 FROM checklist_items
+                WHERE task_id = tasks.id
+            ), 0)
+        FROM tasks
+        WHERE tasks.id = $1
+        RETURNING
+            id,
+            task_id,
+            title,
+            done,
+            position,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+    "#)
+        .bind(task_id)
+        .bind(create_dto.title)
+        .bind(create_dto.done.unwrap_or(false))
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(item) => return Ok(item),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+/// Appends items to the checklist of a task, inside the caller's
+/// transaction. `titles` and `done` are read in pairs; access to the task is
+/// up to the caller.
+pub async fn add_items(
+    task_id: i32,
+    titles: &[String],
+    done: &[bool],
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        INSERT INTO checklist_items (task_id, title, done, position)
+        SELECT
+            $1,
+            items.title,
+            items.done,
+            COALESCE((
+                SELECT MAX(position) + 1 FROM checklist_items
+                WHERE task_id = $1
+            ), 0) + items.ordinality - 1
+        FROM unnest($2::TEXT[], $3::BOOLEAN[]) WITH ORDINALITY AS items(title, done, ordinality)
+    "#)
+        .bind(task_id)
+        .bind(titles)
+        .bind(done)
+        .execute(conn)
+        .await;
+    if let Err(e) = result {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+pub async fn get_all(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<ChecklistItem>, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let result = sqlx::query_as::<_, ChecklistItem>(r#"
+        SELECT
+            checklist_items.id,
+            checklist_items.task_id,
+            checklist_items.title,
+            checklist_items.done,
+            checklist_items.position,
+            to_char(checklist_items.created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(checklist_items.updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+        FROM checklist_items
+        WHERE checklist_items.task_id = $1
+        ORDER BY checklist_items.position, checklist_items.id;
+    "#)
+        .bind(task_id)
+        .fetch_all(&mut *conn)
+        .await;
+    match result {
+        Ok(items) => return Ok(items),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn update(
+    update_dto: UpdateDto,
+    id: i32,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<ChecklistItem, AppError> {
+    if  update_dto.title.is_none() &&
+        update_dto.done.is_none()  &&
+        update_dto.position.is_none() {
+        return Err(AppError::BadRequest);
+    }
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut conn).await?;
+    let result = sqlx::query_as::<_, ChecklistItem>(r#"
+        UPDATE checklist_items
+        SET
+            title = CASE
+                WHEN LENGTH($1) > 0 THEN $1
+                ELSE title
+            END,
+            done       = COALESCE($2, done),
+            position   = COALESCE($3, position),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            id      = $4 AND
+            task_id = $5
+        RETURNING
+            id,
+            task_id,
+            title,
+            done,
+            position,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+    "#)
+        .bind( & if update_dto.title.is_some() { update_dto.title.unwrap() } else { "".to_string() })
+        .bind(update_dto.done)
+        .bind(update_dto.position)
+        .bind(id)
+        .bind(task_id)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(item) => return Ok(item),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+pub async fn delete(
+    id: i32,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut conn).await?;
+    let result = sqlx::query(r#"
+        DELETE FROM checklist_items
+        WHERE
+            id      = $1 AND
+            task_id = $2
+    "#)
+        .bind(id)
+        .bind(task_id)
+        .execute(&mut *conn)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
\ No newline at end of file
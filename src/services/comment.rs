@@ -0,0 +1,217 @@
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        comment::{
+            Comment,
+            CreateDto,
+            UpdateDto
+        },
+        share::Rank
+    },
+    services,
+    error::AppError
+};
+
+const COMMENT_COLUMNS: &str = r#"
+    comments.id,
+    comments.task_id,
+    comments.user_id,
+    users.username,
+    comments.body,
+    to_char(comments.created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+    to_char(comments.updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+"#;
+
+/// Anyone who can see the task can comment on it.
+pub async fn create(
+    create_dto: CreateDto,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Comment, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let result = sqlx::query_as::<_, Comment>(&format!(r#"
+        WITH comments AS (
+            INSERT INTO comments (task_id, user_id, body)
+            VALUES ( $1, $2, $3 )
+            RETURNING *
+        )
+        SELECT {COMMENT_COLUMNS}
+        FROM comments
+        JOIN users ON users.id = comments.user_id
+    "#))
+        .bind(task_id)
+        .bind(user_id)
+        .bind(create_dto.body)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(comment) => return Ok(comment),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn get_all(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Comment>, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let result = sqlx::query_as::<_, Comment>(&format!(r#"
+        SELECT {COMMENT_COLUMNS}
+        FROM comments
+        JOIN users ON users.id = comments.user_id
+        WHERE comments.task_id = $1
+        ORDER BY comments.id;
+    "#))
+        .bind(task_id)
+        .fetch_all(&mut *conn)
+        .await;
+    match result {
+        Ok(comments) => return Ok(comments),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Only the author can edit a comment.
+pub async fn update(
+    update_dto: UpdateDto,
+    id: i32,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Comment, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let author_id = find_author(id, task_id, &mut conn).await?;
+    if author_id != user_id {
+        return Err(AppError::Forbidden);
+    }
+    let result = sqlx::query_as::<_, Comment>(&format!(r#"
+        WITH comments AS (
+            UPDATE comments
+            SET
+                body       = $1,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $2
+            RETURNING *
+        )
+        SELECT {COMMENT_COLUMNS}
+        FROM comments
+        JOIN users ON users.id = comments.user_id
+    "#))
+        .bind(update_dto.body)
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(comment) => return Ok(comment),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+/// The author can delete their comment, and task owners can moderate the
+/// thread by deleting anyone's.
+pub async fn delete(
+    id: i32,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let author_id = find_author(id, task_id, &mut conn).await?;
+    if author_id != user_id {
+        services::share::check_task(task_id, user_id, Rank::Owner, &mut conn).await?;
+    }
+    let result = sqlx::query(r#"
+        DELETE FROM comments
+        WHERE id = $1
+    "#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+async fn find_author(
+    id: i32,
+    task_id: i32,
+    conn: &mut PgConnection
+) -> Result<i32, AppError> {
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT user_id FROM comments
+        WHERE
+            id      = $1 AND
+            task_id = $2
+    "#)
+        .bind(id)
+        .bind(task_id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(author_id) => return Ok(author_id),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,167 @@
+use sqlx::{
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        dependency::CreateDto,
+        share::Rank,
+        task::Task
+    },
+    services::{
+        self,
+        task::TASK_COLUMNS
+    },
+    error::AppError
+};
+
+/// Tasks that block `task_id`, done or not.
+pub async fn get_all(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Task>, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let result = sqlx::query_as::<_, Task>(&format!(r#"
+        SELECT {TASK_COLUMNS}
+        FROM tasks
+        WHERE
+            id IN (SELECT task_id FROM task_access WHERE user_id = $2) AND
+            id IN (
+                SELECT blocked_by_id FROM task_dependencies
+                WHERE task_id = $1
+            )
+        ORDER BY rank, id;
+    "#))
+        .bind(task_id)
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await;
+    match result {
+        Ok(tasks) => return Ok(tasks),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Makes `task_id` wait for `blocked_by_id`. Rejected when `task_id`
+/// already blocks `blocked_by_id`, directly or through other tasks.
+pub async fn create(
+    create_dto: CreateDto,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Task>, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut tx).await?;
+    services::share::check_task(create_dto.blocked_by_id, user_id, Rank::Viewer, &mut tx).await
+        .map_err(|_| AppError::ValidationError("blocked_by_id: task NOT found".to_string()))?;
+
+    // Two edges added at once could close a cycle neither of them sees.
+    let locked = sqlx::query(r#"
+        LOCK TABLE task_dependencies IN SHARE ROW EXCLUSIVE MODE
+    "#)
+        .execute(&mut *tx)
+        .await;
+    if let Err(e) = locked {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    let cycle = sqlx::query_scalar::<_, bool>(r#"
+        WITH RECURSIVE blockers AS (
+            SELECT $2::int AS id
+            UNION
+            SELECT task_dependencies.blocked_by_id
+            FROM task_dependencies
+            JOIN blockers ON blockers.id = task_dependencies.task_id
+        )
+        SELECT EXISTS (SELECT 1 FROM blockers WHERE id = $1)
+    "#)
+        .bind(task_id)
+        .bind(create_dto.blocked_by_id)
+        .fetch_one(&mut *tx)
+        .await;
+    match cycle {
+        Ok(true) => return Err(AppError::ValidationError(
+            "blocked_by_id: would create a dependency cycle".to_string()
+        )),
+        Ok(false) => {}
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+    let result = sqlx::query(r#"
+        INSERT INTO task_dependencies (task_id, blocked_by_id)
+        VALUES ( $1, $2 )
+        ON CONFLICT DO NOTHING
+    "#)
+        .bind(task_id)
+        .bind(create_dto.blocked_by_id)
+        .execute(&mut *tx)
+        .await;
+    if let Err(e) = result {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return get_all(task_id, user_id, pool).await;
+}
+
+pub async fn delete(
+    blocked_by_id: i32,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut conn).await?;
+    let result = sqlx::query(r#"
+        DELETE FROM task_dependencies
+        WHERE
+            task_id       = $1 AND
+            blocked_by_id = $2
+    "#)
+        .bind(task_id)
+        .bind(blocked_by_id)
+        .execute(&mut *conn)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,171 @@
+use sha2::{
+    Digest,
+    Sha256
+};
+use sqlx::{
+    Pool,
+    Postgres
+};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    modules::feed::{
+        FeedTask,
+        FeedToken
+    },
+    ical,
+    error::AppError
+};
+
+/// Columns read into `FeedTask`, for `tasks` left-joined with
+/// `caldav_objects`. Tasks never written over CalDAV get a name and UID
+/// derived from their id.
+pub const CALENDAR_COLUMNS: &str = r#"
+    tasks.id,
+    COALESCE(caldav_objects.name, 'task-' || tasks.id || '.ics') as name,
+    COALESCE(caldav_objects.uid, 'task-' || tasks.id || '@todolistify') as uid,
+    tasks.title,
+    COALESCE(tasks.body, '') as body,
+    tasks.closed,
+    (NOT tasks.closed AND EXISTS (
+        SELECT 1 FROM workflow_states AS current
+        JOIN workflow_states AS earlier ON
+            earlier.workflow_id = current.workflow_id AND
+            earlier.position    < current.position
+        WHERE current.id = tasks.state_id
+    )) as started,
+    tasks.priority,
+    tasks.version,
+    to_char(tasks.start_at at time zone 'UTC', 'YYYYMMDD"T"HH24MISS"Z"') as start_at,
+    to_char(tasks.due_at at time zone 'UTC', 'YYYYMMDD"T"HH24MISS"Z"') as due_at,
+    COALESCE((
+        SELECT json_agg(tags.name ORDER BY tags.name)
+        FROM task_tags
+        JOIN tags ON tags.id = task_tags.tag_id
+        WHERE task_tags.task_id = tasks.id
+    ), '[]') as tags,
+    to_char(tasks.created_at at time zone 'UTC', 'YYYYMMDD"T"HH24MISS"Z"') as created_at,
+    to_char(tasks.updated_at at time zone 'UTC', 'YYYYMMDD"T"HH24MISS"Z"') as updated_at
+"#;
+
+/// Creates the user's feed token, replacing and so revoking any older one.
+pub async fn create_token(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<FeedToken, AppError> {
+    let token = format!(
+        "{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    );
+    let result = sqlx::query(r#"
+        INSERT INTO feed_tokens (user_id, token_hash)
+        VALUES ( $1, $2 )
+        ON CONFLICT (user_id) DO UPDATE
+        SET
+            token_hash = EXCLUDED.token_hash,
+            created_at = CURRENT_TIMESTAMP
+    "#)
+        .bind(user_id)
+        .bind(hash_token(&token))
+        .execute(pool)
+        .await;
+    match result {
+        Ok(_) => return Ok(FeedToken {
+            url: format!("/api/v1/feed/{}.ics", token),
+            token
+        }),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn delete_token(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        DELETE FROM feed_tokens
+        WHERE user_id = $1
+    "#)
+        .bind(user_id)
+        .execute(pool)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// The live tasks visible to the token's owner as a VCALENDAR of VTODOs.
+/// Tokens of inactive accounts are unknown.
+pub async fn render(
+    token: &str,
+    pool: &Pool<Postgres>
+) -> Result<String, AppError> {
+    let result = sqlx::query_as::<_, FeedTask>(&format!(r#"
+        SELECT {CALENDAR_COLUMNS}
+        FROM tasks
+        LEFT JOIN caldav_objects ON caldav_objects.task_id = tasks.id
+        WHERE tasks.id IN (
+            SELECT task_id FROM task_access
+            WHERE user_id = (
+                SELECT feed_tokens.user_id FROM feed_tokens
+                JOIN users ON users.id = feed_tokens.user_id
+                WHERE
+                    feed_tokens.token_hash = $1 AND
+                    users.state            = 'active'
+            )
+        )
+        ORDER BY tasks.rank, tasks.id
+    "#))
+        .bind(hash_token(token))
+        .fetch_all(pool)
+        .await;
+    let tasks = match result {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    // An unknown token and an empty account would otherwise look the same.
+    if tasks.is_empty() {
+        let exists = sqlx::query_scalar::<_, bool>(r#"
+            SELECT EXISTS (
+                SELECT 1 FROM feed_tokens
+                JOIN users ON users.id = feed_tokens.user_id
+                WHERE
+                    feed_tokens.token_hash = $1 AND
+                    users.state            = 'active'
+            )
+        "#)
+            .bind(hash_token(token))
+            .fetch_one(pool)
+            .await;
+        match exists {
+            Ok(true) => {}
+            Ok(false) => return Err(AppError::NotFoundData),
+            Err(e) => {
+                error!("{:#?}", e);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+    return Ok(ical::calendar(&tasks));
+}
+
+pub fn hash_token(token: &str) -> String {
+    return format!("{:x}", Sha256::digest(token.as_bytes()));
+}
\ No newline at end of file
@@ -0,0 +1,80 @@
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        history::HistoryEntry,
+        share::Rank
+    },
+    services,
+    error::AppError
+};
+
+/// Tells the `record_task_history` trigger who is changing tasks for the
+/// rest of the current transaction. Must be called inside a transaction,
+/// otherwise the setting would leak to the next user of the connection.
+pub async fn set_actor(
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        SELECT set_config('todolistify.user_id', $1, true)
+    "#)
+        .bind(user_id.to_string())
+        .execute(conn)
+        .await;
+    if let Err(e) = result {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+/// History of a task, in the trash or not.
+pub async fn get_all(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<HistoryEntry>, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    match services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await {
+        Err(AppError::NotFoundData) => {
+            services::share::check_trash(task_id, user_id, Rank::Viewer, &mut conn).await?;
+        }
+        result => result?
+    }
+    let result = sqlx::query_as::<_, HistoryEntry>(r#"
+        SELECT
+            task_history.id,
+            task_history.task_id,
+            task_history.user_id,
+            users.username,
+            task_history.action,
+            task_history.changes,
+            to_char(task_history.created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at
+        FROM task_history
+        LEFT JOIN users ON users.id = task_history.user_id
+        WHERE task_history.task_id = $1
+        ORDER BY task_history.id;
+    "#)
+        .bind(task_id)
+        .fetch_all(&mut *conn)
+        .await;
+    match result {
+        Ok(entries) => return Ok(entries),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,196 @@
+use sqlx::{
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::project::{
+        CreateDto,
+        DeleteMode,
+        Project,
+        UpdateDto
+    },
+    modules::share::Rank,
+    services,
+    error::AppError
+};
+
+pub async fn create(
+    create_dto: CreateDto,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Project, AppError> {
+    let result = sqlx::query_as::<_, Project>(r#"
+        INSERT INTO projects (user_id, name)
+        VALUES ( $1, $2 )
+        RETURNING
+            id,
+            user_id,
+            name,
+            archived,
+            workflow_id,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+    "#)
+        .bind(user_id)
+        .bind(create_dto.name)
+        .fetch_one(pool)
+        .await;
+    match result {
+        Ok(project) => return Ok(project),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn get_all(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Project>, AppError> {
+    let result = sqlx::query_as::<_, Project>(r#"
+        SELECT
+            id,
+            user_id,
+            name,
+            archived,
+            workflow_id,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+        FROM projects
+        WHERE id IN (SELECT project_id FROM project_access WHERE user_id = $1)
+        ORDER BY archived, name, id;
+    "#)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(projects) => return Ok(projects),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn update(
+    update_dto: UpdateDto,
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Project, AppError> {
+    if update_dto.name.is_none() && update_dto.archived.is_none() {
+        return Err(AppError::BadRequest);
+    }
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_project(id, user_id, Rank::Editor, &mut conn).await?;
+    let result = sqlx::query_as::<_, Project>(r#"
+        UPDATE projects
+        SET
+            name = CASE
+                WHEN LENGTH($1) > 0 THEN $1
+                ELSE name
+            END,
+            archived   = COALESCE($2, archived),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $3
+        RETURNING
+            id,
+            user_id,
+            name,
+            archived,
+            workflow_id,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+    "#)
+        .bind( & if update_dto.name.is_some() { update_dto.name.unwrap() } else { "".to_string() })
+        .bind(update_dto.archived)
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(project) => return Ok(project),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+/// Deletes the project and, depending on `mode`, either moves its tasks to
+/// the trash or just drops their link to it so they fall back to the inbox.
+/// Trashed tasks lose the project either way. Only owners can do it.
+pub async fn delete(
+    id: i32,
+    user_id: i32,
+    mode: DeleteMode,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    services::share::check_project(id, user_id, Rank::Owner, &mut tx).await?;
+    let tasks_sql = match mode {
+        DeleteMode::Cascade => r#"
+            UPDATE tasks
+            SET
+                project_id = NULL,
+                deleted_at = COALESCE(deleted_at, CURRENT_TIMESTAMP)
+            WHERE project_id = $1
+        "#,
+        DeleteMode::Inbox => r#"
+            UPDATE tasks
+            SET
+                project_id = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE project_id = $1
+        "#
+    };
+    let tasks_result = sqlx::query(tasks_sql)
+        .bind(id)
+        .execute(&mut *tx)
+        .await;
+    if let Err(e) = tasks_result {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    let result = sqlx::query(r#"
+        DELETE FROM projects
+        WHERE id = $1
+    "#)
+        .bind(id)
+        .execute(&mut *tx)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() == 0 {
+                return Err(AppError::NotFoundData);
+            }
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
\ No newline at end of file
@@ -0,0 +1,302 @@
+use chrono::NaiveDateTime;
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        recurrence::{
+            next_occurrence,
+            Occurrence,
+            RecurrenceDto
+        },
+        share::Rank,
+        task::Task
+    },
+    services::{
+        self,
+        task::find
+    },
+    error::AppError
+};
+
+/// Makes the task recurring, or replaces the rule of its series. The
+/// task's title, body and priority become the template of later occurrences.
+pub async fn set(
+    recurrence_dto: RecurrenceDto,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Task, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut tx).await?;
+    let task = find(task_id, user_id, &mut tx).await?;
+    save_rule(recurrence_dto, task_id, task.series_id, &mut tx).await?;
+    let task = find(task_id, user_id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
+}
+
+/// Writes the rule to the task's series `series_id`, creating the series
+/// from the task when it has none yet.
+pub async fn save_rule(
+    recurrence_dto: RecurrenceDto,
+    task_id: i32,
+    series_id: Option<i32>,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = match series_id {
+        Some(series_id) => sqlx::query(r#"
+            UPDATE task_series
+            SET
+                frequency  = $1,
+                every      = $2,
+                weekdays   = $3,
+                month_day  = $4,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $5
+        "#)
+            .bind(&recurrence_dto.frequency)
+            .bind(recurrence_dto.every.unwrap_or(1))
+            .bind(&recurrence_dto.weekdays)
+            .bind(recurrence_dto.month_day)
+            .bind(series_id)
+            .execute(&mut *conn)
+            .await,
+        None => sqlx::query(r#"
+            WITH series AS (
+                INSERT INTO task_series (user_id, frequency, every, weekdays, month_day, title, body, priority)
+                SELECT user_id, $1, $2, $3, $4, title, body, priority
+                FROM tasks
+                WHERE id = $5
+                RETURNING id
+            )
+            UPDATE tasks
+            SET series_id = (SELECT id FROM series)
+            WHERE id = $5
+        "#)
+            .bind(&recurrence_dto.frequency)
+            .bind(recurrence_dto.every.unwrap_or(1))
+            .bind(&recurrence_dto.weekdays)
+            .bind(recurrence_dto.month_day)
+            .bind(task_id)
+            .execute(&mut *conn)
+            .await
+    };
+    if let Err(e) = result {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+/// Detaches the task from its series so completing it no longer creates
+/// a next occurrence. Earlier occurrences keep their link.
+pub async fn stop(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut tx).await?;
+    let result = sqlx::query(r#"
+        UPDATE tasks
+        SET series_id = NULL
+        WHERE
+            id = $1 AND
+            series_id IS NOT NULL
+    "#)
+        .bind(task_id)
+        .execute(&mut *tx)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() == 0 {
+                return Err(AppError::NotFoundData);
+            }
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+/// Creates the occurrence following the completed task `task_id`, copying
+/// its project, members, tags and unticked checklist. Nothing is created
+/// while the series still has another open occurrence. Dates are computed
+/// in the timezone of the task owner, whoever closed the task.
+pub async fn spawn_next(
+    task_id: i32,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query_as::<_, Occurrence>(r#"
+        SELECT
+            task_series.frequency,
+            task_series.every,
+            task_series.weekdays,
+            task_series.month_day,
+            users.timezone,
+            tasks.due_at   AT TIME ZONE users.timezone as due_local,
+            tasks.start_at AT TIME ZONE users.timezone as start_local,
+            CURRENT_TIMESTAMP AT TIME ZONE users.timezone as now_local
+        FROM tasks
+        JOIN task_series ON task_series.id = tasks.series_id
+        JOIN users ON users.id = tasks.user_id
+        WHERE
+            tasks.id = $1 AND
+            NOT EXISTS (
+                SELECT 1 FROM tasks AS open
+                WHERE
+                    open.series_id = tasks.series_id AND
+                    open.id       <> tasks.id AND
+                    NOT open.closed AND
+                    open.deleted_at IS NULL
+            )
+    "#)
+        .bind(task_id)
+        .fetch_optional(&mut *conn)
+        .await;
+    let occurrence = match result {
+        Ok(Some(occurrence)) => occurrence,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+
+    let base = occurrence.due_local.unwrap_or(occurrence.now_local);
+    let due_local = next_occurrence(&occurrence.rule, base, occurrence.now_local);
+    let start_local: Option<NaiveDateTime> = match (occurrence.start_local, occurrence.due_local) {
+        (Some(start), Some(due)) => Some(due_local - (due - start)),
+        _ => None
+    };
+
+    let inserted = sqlx::query_scalar::<_, i32>(r#"
+        INSERT INTO tasks (user_id, project_id, series_id, title, body, state, priority, start_at, due_at)
+        SELECT
+            tasks.user_id,
+            tasks.project_id,
+            task_series.id,
+            task_series.title,
+            task_series.body,
+            NULL,
+            task_series.priority,
+            $2 AT TIME ZONE $4,
+            $3 AT TIME ZONE $4
+        FROM tasks
+        JOIN task_series ON task_series.id = tasks.series_id
+        WHERE tasks.id = $1
+        RETURNING id
+    "#)
+        .bind(task_id)
+        .bind(start_local)
+        .bind(due_local)
+        .bind(&occurrence.timezone)
+        .fetch_one(&mut *conn)
+        .await;
+    let next_id = match inserted {
+        Ok(id) => id,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+
+    let copied = sqlx::query(r#"
+        WITH copied_members AS (
+            INSERT INTO memberships (user_id, task_id, role, state, invited_by)
+            SELECT user_id, $2, role, state, invited_by FROM memberships
+            WHERE task_id = $1
+            ON CONFLICT DO NOTHING
+        ), copied_tags AS (
+            INSERT INTO task_tags (task_id, tag_id)
+            SELECT $2, tag_id FROM task_tags
+            WHERE task_id = $1
+        )
+        INSERT INTO checklist_items (task_id, title, position)
+        SELECT $2, title, position FROM checklist_items
+        WHERE task_id = $1
+    "#)
+        .bind(task_id)
+        .bind(next_id)
+        .execute(&mut *conn)
+        .await;
+    if let Err(e) = copied {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+/// Carries the edits made to `task_id` over to its series template and the
+/// later open occurrences of the series.
+pub async fn apply_to_future(
+    task_id: i32,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        WITH source AS (
+            SELECT id, series_id, title, body, priority
+            FROM tasks
+            WHERE
+                id = $1 AND
+                series_id IS NOT NULL
+        ), series AS (
+            UPDATE task_series
+            SET
+                title      = source.title,
+                body       = source.body,
+                priority   = source.priority,
+                updated_at = CURRENT_TIMESTAMP
+            FROM source
+            WHERE task_series.id = source.series_id
+        )
+        UPDATE tasks
+        SET
+            title      = source.title,
+            body       = source.body,
+            priority   = source.priority,
+            updated_at = CURRENT_TIMESTAMP
+        FROM source
+        WHERE
+            tasks.series_id = source.series_id AND
+            tasks.id        > source.id AND
+            NOT tasks.closed AND
+            tasks.deleted_at IS NULL
+    "#)
+        .bind(task_id)
+        .execute(&mut *conn)
+        .await;
+    if let Err(e) = result {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
\ No newline at end of file
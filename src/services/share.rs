@@ -0,0 +1,447 @@
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::share::{
+        InviteDto,
+        Membership,
+        Rank,
+        Target,
+        UpdateDto
+    },
+    error::AppError
+};
+
+const MEMBERSHIP_COLUMNS: &str = r#"
+    memberships.id,
+    memberships.user_id,
+    users.username,
+    memberships.task_id,
+    memberships.project_id,
+    memberships.role,
+    memberships.state,
+    memberships.invited_by,
+    to_char(memberships.created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+    to_char(memberships.updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+"#;
+
+/// Fails with `NotFoundData` when the user cannot see the task at all and
+/// with `Forbidden` when they can, but with a lower role than `rank`.
+pub async fn check_task(
+    task_id: i32,
+    user_id: i32,
+    rank: Rank,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT rank FROM task_access
+        WHERE
+            task_id = $1 AND
+            user_id = $2
+    "#)
+        .bind(task_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await;
+    return check_rank(result, rank);
+}
+
+/// Same as `check_task`, for tasks in the trash.
+pub async fn check_trash(
+    task_id: i32,
+    user_id: i32,
+    rank: Rank,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT rank FROM trash_access
+        WHERE
+            task_id = $1 AND
+            user_id = $2
+    "#)
+        .bind(task_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await;
+    return check_rank(result, rank);
+}
+
+/// Same as `check_task`, for projects.
+pub async fn check_project(
+    project_id: i32,
+    user_id: i32,
+    rank: Rank,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT rank FROM project_access
+        WHERE
+            project_id = $1 AND
+            user_id    = $2
+    "#)
+        .bind(project_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await;
+    return check_rank(result, rank);
+}
+
+fn check_rank(
+    result: Result<Option<i32>, sqlx::Error>,
+    rank: Rank
+) -> Result<(), AppError> {
+    match result {
+        Ok(Some(found)) => {
+            if found < rank as i32 {
+                return Err(AppError::Forbidden);
+            }
+            return Ok(());
+        }
+        Ok(None) => return Err(AppError::NotFoundData),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+async fn check_target(
+    target: Target,
+    user_id: i32,
+    rank: Rank,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    match target {
+        Target::Task(task_id) => return check_task(task_id, user_id, rank, conn).await,
+        Target::Project(project_id) => return check_project(project_id, user_id, rank, conn).await
+    }
+}
+
+/// Invites `username` to the task or project. Only owners can invite and
+/// the membership stays `PENDING` until the recipient accepts it.
+pub async fn invite(
+    invite_dto: InviteDto,
+    target: Target,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Membership, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    check_target(target, user_id, Rank::Owner, &mut conn).await?;
+    let (task_id, project_id) = match target {
+        Target::Task(task_id) => (Some(task_id), None),
+        Target::Project(project_id) => (None, Some(project_id))
+    };
+    let result = sqlx::query_as::<_, Membership>(&format!(r#"
+        WITH memberships AS (
+            INSERT INTO memberships (user_id, task_id, project_id, role, invited_by)
+            SELECT id, $2, $3, $4, $5 FROM users
+            WHERE
+                username = $1 AND
+                state    = 'active' AND
+                id      <> $5
+            RETURNING *
+        )
+        SELECT {MEMBERSHIP_COLUMNS}
+        FROM memberships
+        JOIN users ON users.id = memberships.user_id
+    "#))
+        .bind(&invite_dto.username)
+        .bind(task_id)
+        .bind(project_id)
+        .bind(&invite_dto.role)
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(membership) => return Ok(membership),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundUser),
+            sqlx::Error::Database(db_err) => {
+                if let Some(err_code) = db_err.code() {
+                    if err_code == "23505" {
+                        return Err(AppError::ValidationError(
+                            "username: already a member".to_string()
+                        ));
+                    }
+                }
+                error!("{:#?}", db_err);
+                return Err(AppError::InternalServer);
+            }
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+pub async fn get_members(
+    target: Target,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Membership>, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    check_target(target, user_id, Rank::Viewer, &mut conn).await?;
+    let (task_id, project_id) = match target {
+        Target::Task(task_id) => (Some(task_id), None),
+        Target::Project(project_id) => (None, Some(project_id))
+    };
+    let result = sqlx::query_as::<_, Membership>(&format!(r#"
+        SELECT {MEMBERSHIP_COLUMNS}
+        FROM memberships
+        JOIN users ON users.id = memberships.user_id
+        WHERE
+            memberships.task_id    IS NOT DISTINCT FROM $1 AND
+            memberships.project_id IS NOT DISTINCT FROM $2
+        ORDER BY memberships.id;
+    "#))
+        .bind(task_id)
+        .bind(project_id)
+        .fetch_all(&mut *conn)
+        .await;
+    match result {
+        Ok(memberships) => return Ok(memberships),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Pending invitations addressed to the user.
+pub async fn get_invitations(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Membership>, AppError> {
+    let result = sqlx::query_as::<_, Membership>(&format!(r#"
+        SELECT {MEMBERSHIP_COLUMNS}
+        FROM memberships
+        JOIN users ON users.id = memberships.user_id
+        WHERE
+            memberships.user_id = $1 AND
+            memberships.state   = 'PENDING'
+        ORDER BY memberships.id;
+    "#))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(memberships) => return Ok(memberships),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn accept(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Membership, AppError> {
+    let result = sqlx::query_as::<_, Membership>(&format!(r#"
+        WITH memberships AS (
+            UPDATE memberships
+            SET
+                state      = 'ACCEPTED',
+                updated_at = CURRENT_TIMESTAMP
+            WHERE
+                id      = $1 AND
+                user_id = $2 AND
+                state   = 'PENDING'
+            RETURNING *
+        )
+        SELECT {MEMBERSHIP_COLUMNS}
+        FROM memberships
+        JOIN users ON users.id = memberships.user_id
+    "#))
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await;
+    match result {
+        Ok(membership) => return Ok(membership),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+pub async fn decline(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        DELETE FROM memberships
+        WHERE
+            id      = $1 AND
+            user_id = $2 AND
+            state   = 'PENDING'
+    "#)
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Changes the role of a member. Only owners can do it, and the creator of
+/// the task or project always stays its owner.
+pub async fn update(
+    update_dto: UpdateDto,
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Membership, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let (target, member_id, creator_id) = find_target(id, &mut conn).await?;
+    check_target(target, user_id, Rank::Owner, &mut conn).await?;
+    if member_id == creator_id {
+        return Err(AppError::Forbidden);
+    }
+    let result = sqlx::query_as::<_, Membership>(&format!(r#"
+        WITH memberships AS (
+            UPDATE memberships
+            SET
+                role       = $1,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $2
+            RETURNING *
+        )
+        SELECT {MEMBERSHIP_COLUMNS}
+        FROM memberships
+        JOIN users ON users.id = memberships.user_id
+    "#))
+        .bind(&update_dto.role)
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(membership) => return Ok(membership),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+/// Removes a membership: owners can revoke anyone but the creator, and
+/// members can always leave.
+pub async fn delete(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let (target, member_id, creator_id) = find_target(id, &mut conn).await?;
+    if member_id == creator_id {
+        return Err(AppError::Forbidden);
+    }
+    if member_id != user_id {
+        check_target(target, user_id, Rank::Owner, &mut conn).await?;
+    }
+    let result = sqlx::query(r#"
+        DELETE FROM memberships
+        WHERE id = $1
+    "#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Returns what the membership `id` grants access to, its member and the
+/// creator of that task or project.
+async fn find_target(
+    id: i32,
+    conn: &mut PgConnection
+) -> Result<(Target, i32, i32), AppError> {
+    let result = sqlx::query_as::<_, (Option<i32>, Option<i32>, i32, i32)>(r#"
+        SELECT
+            memberships.task_id,
+            memberships.project_id,
+            memberships.user_id,
+            COALESCE(tasks.user_id, projects.user_id)
+        FROM memberships
+        LEFT JOIN tasks ON tasks.id = memberships.task_id
+        LEFT JOIN projects ON projects.id = memberships.project_id
+        WHERE memberships.id = $1
+    "#)
+        .bind(id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok((Some(task_id), _, member_id, creator_id)) => {
+            return Ok((Target::Task(task_id), member_id, creator_id));
+        }
+        Ok((None, Some(project_id), member_id, creator_id)) => {
+            return Ok((Target::Project(project_id), member_id, creator_id));
+        }
+        Ok(_) => return Err(AppError::NotFoundData),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
\ No newline at end of file
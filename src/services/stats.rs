@@ -0,0 +1,273 @@
+use chrono::{
+    Duration,
+    NaiveDate
+};
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::stats::{
+        Bucket,
+        CompletedCount,
+        PriorityCount,
+        StateCount,
+        Stats,
+        StatsQuery,
+        MAX_RANGE_DAYS
+    },
+    error::AppError
+};
+
+/// Live tasks visible to user `$1`, of project `$2` when it is not null.
+const STATS_SCOPE: &str = r#"
+    tasks.id IN (SELECT task_id FROM task_access WHERE user_id = $1) AND
+    ($2::INT IS NULL OR tasks.project_id = $2)
+"#;
+
+/// The day, in timezone `$3`, a task was completed on.
+const COMPLETED_DAY: &str = "((tasks.completed_at AT TIME ZONE 'UTC') AT TIME ZONE $3)::DATE";
+
+pub async fn get(
+    stats_query: StatsQuery,
+    user_id: i32,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<Stats, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let today = today(timezone, &mut conn).await?;
+    let (from, to) = date_range(
+        stats_query.from.as_deref(),
+        stats_query.to.as_deref(),
+        today
+    )?;
+    let bucket = stats_query.bucket.unwrap_or(Bucket::Day);
+    let project_id = stats_query.project_id;
+
+    let by_state = sqlx::query_as::<_, StateCount>(&format!(r#"
+        SELECT state, COUNT(*) as count
+        FROM tasks
+        WHERE {STATS_SCOPE}
+        GROUP BY state
+        ORDER BY state
+    "#))
+        .bind(user_id)
+        .bind(project_id)
+        .fetch_all(&mut *conn)
+        .await;
+    let by_state = match by_state {
+        Ok(by_state) => by_state,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+
+    let by_priority = sqlx::query_as::<_, PriorityCount>(&format!(r#"
+        SELECT priority, COUNT(*) as count
+        FROM tasks
+        WHERE {STATS_SCOPE}
+        GROUP BY priority
+        ORDER BY priority
+    "#))
+        .bind(user_id)
+        .bind(project_id)
+        .fetch_all(&mut *conn)
+        .await;
+    let by_priority = match by_priority {
+        Ok(by_priority) => by_priority,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+
+    // Every bucket of the range is listed, empty ones with a count of 0.
+    let completed = sqlx::query_as::<_, CompletedCount>(&format!(r#"
+        SELECT
+            to_char(buckets.start, 'YYYY-MM-DD') as date,
+            COUNT(done.day) as count
+        FROM generate_series(
+            date_trunc($6, $4::DATE::TIMESTAMP),
+            $5::DATE::TIMESTAMP,
+            ('1 ' || $6)::INTERVAL
+        ) AS buckets(start)
+        LEFT JOIN (
+            SELECT {COMPLETED_DAY} as day
+            FROM tasks
+            WHERE
+                {STATS_SCOPE} AND
+                tasks.completed_at IS NOT NULL AND
+                {COMPLETED_DAY} BETWEEN $4 AND $5
+        ) done ON date_trunc($6, done.day::TIMESTAMP) = buckets.start
+        GROUP BY buckets.start
+        ORDER BY buckets.start
+    "#))
+        .bind(user_id)
+        .bind(project_id)
+        .bind(timezone)
+        .bind(from)
+        .bind(to)
+        .bind(bucket_name(bucket))
+        .fetch_all(&mut *conn)
+        .await;
+    let completed = match completed {
+        Ok(completed) => completed,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+
+    let average_seconds_to_done = sqlx::query_scalar::<_, Option<f64>>(&format!(r#"
+        SELECT AVG(EXTRACT(EPOCH FROM tasks.completed_at - tasks.created_at))::FLOAT8
+        FROM tasks
+        WHERE
+            {STATS_SCOPE} AND
+            tasks.completed_at IS NOT NULL AND
+            {COMPLETED_DAY} BETWEEN $4 AND $5
+    "#))
+        .bind(user_id)
+        .bind(project_id)
+        .bind(timezone)
+        .bind(from)
+        .bind(to)
+        .fetch_one(&mut *conn)
+        .await;
+    let average_seconds_to_done = match average_seconds_to_done {
+        Ok(average) => average,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+
+    let (current_streak, longest_streak) = streaks(user_id, project_id, timezone, today, &mut conn).await?;
+    let total = by_state.iter().map(|state| state.count).sum();
+    return Ok(Stats {
+        from: from.to_string(),
+        to: to.to_string(),
+        bucket,
+        total,
+        by_state,
+        by_priority,
+        completed,
+        average_seconds_to_done,
+        current_streak,
+        longest_streak
+    });
+}
+
+/// The current and the longest run of consecutive days with a completed
+/// task. Each run of days shares `day - row_number`, so grouping on it
+/// gives the runs.
+async fn streaks(
+    user_id: i32,
+    project_id: Option<i32>,
+    timezone: &str,
+    today: NaiveDate,
+    conn: &mut PgConnection
+) -> Result<(i64, i64), AppError> {
+    let result = sqlx::query_as::<_, (i64, i64)>(&format!(r#"
+        WITH days AS (
+            SELECT DISTINCT {COMPLETED_DAY} as day
+            FROM tasks
+            WHERE
+                {STATS_SCOPE} AND
+                tasks.completed_at IS NOT NULL
+        ), runs AS (
+            SELECT MAX(day) as last_day, COUNT(*) as length
+            FROM (
+                SELECT day, day - (ROW_NUMBER() OVER (ORDER BY day))::INT as run
+                FROM days
+            ) numbered
+            GROUP BY run
+        )
+        SELECT
+            COALESCE(MAX(length) FILTER (WHERE last_day >= $4::DATE - 1), 0) as current_streak,
+            COALESCE(MAX(length), 0) as longest_streak
+        FROM runs
+    "#))
+        .bind(user_id)
+        .bind(project_id)
+        .bind(timezone)
+        .bind(today)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(streaks) => return Ok(streaks),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// The current day in `timezone`.
+pub async fn today(
+    timezone: &str,
+    conn: &mut PgConnection
+) -> Result<NaiveDate, AppError> {
+    let today = sqlx::query_scalar::<_, NaiveDate>(r#"
+        SELECT (now() AT TIME ZONE $1)::DATE
+    "#)
+        .bind(timezone)
+        .fetch_one(conn)
+        .await;
+    match today {
+        Ok(today) => return Ok(today),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// The days from `from` to `to`, the 30 days up to `today` by default.
+/// Ranges are at most `MAX_RANGE_DAYS` long.
+pub fn date_range(
+    from: Option<&str>,
+    to: Option<&str>,
+    today: NaiveDate
+) -> Result<(NaiveDate, NaiveDate), AppError> {
+    let to = match to {
+        Some(to) => parse_date(to)?,
+        None => today
+    };
+    let from = match from {
+        Some(from) => parse_date(from)?,
+        None => to - Duration::days(29)
+    };
+    if from > to {
+        return Err(AppError::ValidationError("from: must NOT be after to".to_string()));
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS {
+        return Err(AppError::ValidationError(
+            format!("from: range must NOT exceed {} days", MAX_RANGE_DAYS)
+        ));
+    }
+    return Ok((from, to));
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, AppError> {
+    match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(date) => return Ok(date),
+        Err(_) => return Err(AppError::ValidationError("date must be 'YYYY-MM-DD'".to_string()))
+    }
+}
+
+fn bucket_name(bucket: Bucket) -> &'static str {
+    match bucket {
+        Bucket::Day => return "day",
+        Bucket::Week => return "week"
+    }
+}
\ No newline at end of file
@@ -0,0 +1,163 @@
+use sqlx::{
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::tag::{
+        CreateDto,
+        Tag,
+        UpdateDto
+    },
+    error::AppError
+};
+
+pub async fn create(
+    create_dto: CreateDto,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Tag, AppError> {
+    let result = sqlx::query_as::<_, Tag>(r#"
+        INSERT INTO tags (user_id, name, color)
+        VALUES ( $1, $2, $3 )
+        RETURNING
+            id,
+            user_id,
+            name,
+            color,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+    "#)
+        .bind(user_id)
+        .bind(create_dto.name)
+        .bind( & if create_dto.color.is_some() {create_dto.color.unwrap()} else {"#808080".to_string()} )
+        .fetch_one(pool)
+        .await;
+    match result {
+        Ok(tag) => return Ok(tag),
+        Err(e) => return Err(tag_error(e))
+    }
+}
+
+pub async fn get_all(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Tag>, AppError> {
+    let result = sqlx::query_as::<_, Tag>(r#"
+        SELECT
+            id,
+            user_id,
+            name,
+            color,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+        FROM tags
+        WHERE user_id = $1
+        ORDER BY name;
+    "#)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(tags) => return Ok(tags),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn update(
+    update_dto: UpdateDto,
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Tag, AppError> {
+    if update_dto.name.is_none() && update_dto.color.is_none() {
+        return Err(AppError::BadRequest);
+    }
+    let result = sqlx::query_as::<_, Tag>(r#"
+        UPDATE tags
+        SET
+            name = CASE
+                WHEN LENGTH($1) > 0 THEN $1
+                ELSE name
+            END,
+            color = CASE
+                WHEN LENGTH($2) > 0 THEN $2
+                ELSE color
+            END,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            user_id = $3 AND
+            id      = $4
+        RETURNING
+            id,
+            user_id,
+            name,
+            color,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+    "#)
+        .bind( & if update_dto.name.is_some() { update_dto.name.unwrap() } else { "".to_string() })
+        .bind( & if update_dto.color.is_some() { update_dto.color.unwrap() } else { "".to_string() })
+        .bind(user_id)
+        .bind(id)
+        .fetch_one(pool)
+        .await;
+    match result {
+        Ok(tag) => return Ok(tag),
+        Err(e) => return Err(tag_error(e))
+    }
+}
+
+pub async fn delete(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        DELETE FROM tags
+        WHERE
+            id      = $1 AND
+            user_id = $2
+    "#)
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+fn tag_error(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::RowNotFound => return AppError::NotFoundData,
+        sqlx::Error::Database(db_err) => {
+            if let Some(err_code) = db_err.code() {
+                if err_code == "23505" {
+                    return AppError::ValidationError(
+                        "name: tag already exists".to_string()
+                    );
+                }
+            }
+            error!("{:#?}", db_err);
+            return AppError::InternalServer;
+        }
+        other => {
+            error!("{:#?}", other);
+            return AppError::InternalServer;
+        }
+    }
+}
\ No newline at end of file
@@ -1,76 +1,400 @@
+use base64::{
+    engine::general_purpose::URL_SAFE_NO_PAD,
+    Engine
+};
 use sqlx::{
+    Acquire,
+    PgConnection,
     Pool, 
     Postgres
 };
 use tracing::error;
+use validator::Validate;
 
 use crate::{
     modules::task::{
+        BulkAction,
+        BulkDto,
+        BulkReport,
+        BulkResult,
+        CloneDto,
         CreateDto,
+        MoveDto,
+        QueryDto,
+        ReorderDto,
+        SearchDto,
+        SearchResult,
+        SortField,
+        SortOrder,
+        TagMode,
         Task,
-        UpdateDto
+        TaskPage,
+        UpdateDto,
+        View
+    },
+    modules::{
+        recurrence::Scope,
+        share::Rank
     },
+    services,
     error::AppError
 };
 
+const DEFAULT_PAGE_LIMIT: i64 = 50;
+
+pub const TASK_COLUMNS: &str = r#"
+    id,
+    user_id,
+    project_id,
+    series_id,
+    title,
+    body,
+    state,
+    closed,
+    (SELECT position FROM workflow_states WHERE id = tasks.state_id) as state_position,
+    priority,
+    rank::text as rank,
+    version,
+    to_char(start_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as start_at,
+    to_char(due_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as due_at,
+    (due_at IS NOT NULL AND due_at < CURRENT_TIMESTAMP AND NOT closed) as overdue,
+    EXISTS (
+        SELECT 1 FROM task_dependencies
+        JOIN tasks AS blockers ON blockers.id = task_dependencies.blocked_by_id
+        WHERE
+            task_dependencies.task_id = tasks.id AND
+            NOT blockers.closed AND
+            blockers.deleted_at IS NULL
+    ) as blocked,
+    COALESCE((
+        SELECT json_agg(
+            json_build_object('id', tags.id, 'name', tags.name, 'color', tags.color)
+            ORDER BY tags.name
+        )
+        FROM task_tags
+        JOIN tags ON tags.id = task_tags.tag_id
+        WHERE task_tags.task_id = tasks.id
+    ), '[]') as tags,
+    (
+        SELECT COUNT(*) FROM checklist_items
+        WHERE task_id = tasks.id AND done
+    ) as checklist_done,
+    (
+        SELECT COUNT(*) FROM checklist_items
+        WHERE task_id = tasks.id
+    ) as checklist_total,
+    (
+        SELECT COUNT(*) FROM comments
+        WHERE task_id = tasks.id
+    ) as comment_count,
+    COALESCE((
+        SELECT json_agg(
+            json_build_object(
+                'id', attachments.id,
+                'task_id', attachments.task_id,
+                'user_id', attachments.user_id,
+                'name', attachments.name,
+                'size', attachments.size,
+                'mime_type', attachments.mime_type,
+                'checksum', attachments.checksum,
+                'created_at', to_char(attachments.created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"')
+            )
+            ORDER BY attachments.id
+        )
+        FROM attachments
+        WHERE attachments.task_id = tasks.id
+    ), '[]') as attachments,
+    (
+        SELECT json_build_object(
+            'frequency', frequency,
+            'every', every,
+            'weekdays', weekdays,
+            'month_day', month_day
+        )
+        FROM task_series
+        WHERE task_series.id = tasks.series_id
+    ) as recurrence,
+    to_char(deleted_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as deleted_at,
+    to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at, 
+    to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+"#;
+
 pub async fn create(
     create_dto: CreateDto,
     user_id: i32,
+    timezone: &str,
     pool: &Pool<Postgres>
 ) -> Result<Task, AppError> {
-    let result = sqlx::query_as::<_, Task>(r#"
-        INSERT INTO tasks (user_id, title, body, state, priority)
-        VALUES ( $1, $2, $3, $4, $5 )
-        RETURNING
-            id,
-            user_id,
-            title,
-            body,
-            state,
-            priority,
-            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at, 
-            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
-    "#)
-        .bind(user_id)
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let task = apply_create(create_dto, user_id, timezone, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
+}
+
+/// Same as `create`, inside the caller's transaction. The caller sets the
+/// history actor.
+pub async fn apply_create(
+    create_dto: CreateDto,
+    user_id: i32,
+    timezone: &str,
+    conn: &mut PgConnection
+) -> Result<Task, AppError> {
+    // Tasks added to a shared project belong to the project's creator.
+    let mut owner_id = user_id;
+    if let Some(project_id) = create_dto.project_id {
+        services::share::check_project(project_id, user_id, Rank::Editor, conn).await
+            .map_err(|_| AppError::ValidationError("project_id: project NOT found".to_string()))?;
+        let project = sqlx::query_as::<_, (i32, bool)>(r#"
+            SELECT user_id, archived FROM projects
+            WHERE id = $1
+        "#)
+            .bind(project_id)
+            .fetch_one(&mut *conn)
+            .await;
+        match project {
+            Ok((_, true)) => return Err(archived_error()),
+            Ok((id, false)) => owner_id = id,
+            Err(e) => return Err(task_error(e))
+        }
+    }
+    let start_at = user_timestamp("$6", "$8");
+    let due_at = user_timestamp("$7", "$8");
+    let result = sqlx::query_as::<_, Task>(&format!(r#"
+        INSERT INTO tasks (user_id, title, body, state, priority, start_at, due_at, project_id)
+        VALUES ( $1, $2, $3, $4, $5, {start_at}, {due_at}, $9 )
+        RETURNING {TASK_COLUMNS}
+    "#))
+        .bind(owner_id)
         .bind(create_dto.title)
         .bind( & if create_dto.body.is_some() {create_dto.body.unwrap()} else {"".to_string()} )
-        .bind( & if create_dto.state.is_some() {create_dto.state.unwrap()} else {"TO_DO".to_string()} )
+        .bind(create_dto.state)
         .bind( & if create_dto.priority.is_some() {create_dto.priority.unwrap()} else {"MEDIUM".to_string()} )
-        .fetch_one(pool)
+        .bind(create_dto.start_at)
+        .bind(create_dto.due_at)
+        .bind(timezone)
+        .bind(create_dto.project_id)
+        .fetch_one(&mut *conn)
         .await;
-    match result {
-        Ok(task) => return Ok(task),
+    let mut task = match result {
+        Ok(task) => task,
+        Err(e) => return Err(task_error(e))
+    };
+    if let Some(tag_ids) = create_dto.tag_ids {
+        set_tags(task.id, &tag_ids, conn).await?;
+        task = find(task.id, user_id, conn).await?;
+    }
+    if let Some(recurrence_dto) = create_dto.recurrence {
+        services::recurrence::save_rule(recurrence_dto, task.id, None, conn).await?;
+        task = find(task.id, user_id, conn).await?;
+    }
+    return Ok(task);
+}
+
+/// Copies a task the user can see into a new task, created through
+/// `apply_create` and its rules. The copy keeps the title, body, priority,
+/// dates, project, tags and checklist with its ticks, and starts in the
+/// first state of its workflow. Comments, attachments, dependencies, time,
+/// history and recurrence stay with the original. Tags the new task's owner
+/// does not have are left off.
+pub async fn clone(
+    clone_dto: CloneDto,
+    id: i32,
+    user_id: i32,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<Task, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
         Err(e) => {
             error!("{:#?}", e);
             return Err(AppError::InternalServer);
         }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let source = find(id, user_id, &mut tx).await?;
+    let project_id = clone_dto.project_id.unwrap_or(source.project_id);
+    let source_tag_ids: Vec<i32> = source.tags.iter().map(|tag| tag.id).collect();
+    let tag_ids = owner_tag_ids(&source_tag_ids, project_id, user_id, &mut tx).await?;
+    let create_dto = CreateDto {
+        title: clone_dto.title.unwrap_or(source.title),
+        body: source.body.filter(|body| !body.is_empty()),
+        state: None,
+        priority: Some(source.priority),
+        start_at: source.start_at,
+        due_at: source.due_at,
+        project_id,
+        tag_ids: Some(tag_ids),
+        recurrence: None
+    };
+    if let Err(e) = create_dto.validate() {
+        return Err(AppError::ValidationError(e.to_string()));
     }
+    let task = apply_create(create_dto, user_id, timezone, &mut tx).await?;
+    let items = sqlx::query_as::<_, (String, bool)>(r#"
+        SELECT title, done FROM checklist_items
+        WHERE task_id = $1
+        ORDER BY position, id
+    "#)
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await;
+    let (titles, done): (Vec<String>, Vec<bool>) = match items {
+        Ok(items) => items.into_iter().unzip(),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::checklist::add_items(task.id, &titles, &done, &mut tx).await?;
+    let task = find(task.id, user_id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
 }
 
 pub async fn get_all(
+    query_dto: QueryDto,
     user_id: i32,
+    timezone: &str,
     pool: &Pool<Postgres>
-) -> Result<Vec<Task>, AppError> {
-    let result = sqlx::query_as::<_, Task>(r#"
-        SELECT 
-            id,
-            user_id,
-            title,
-            body,
-            state,
-            priority,
-            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at, 
-            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+) -> Result<TaskPage, AppError> {
+    let sort = query_dto.sort.unwrap_or(SortField::Rank);
+    let order = query_dto.order.unwrap_or(
+        if sort == SortField::Rank { SortOrder::Asc } else { SortOrder::Desc }
+    );
+    let limit = query_dto.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    let mut cursor_id: Option<i32> = None;
+    let mut cursor_value: Option<String> = None;
+    if let Some(cursor) = &query_dto.cursor {
+        match decode_cursor(cursor, sort) {
+            Some((id, value)) => {
+                cursor_id = Some(id);
+                cursor_value = Some(value);
+            }
+            None => return Err(AppError::BadRequest)
+        }
+    }
+
+    let mut tag_ids: Option<Vec<i32>> = None;
+    if let Some(tags) = &query_dto.tags {
+        let mut ids: Vec<i32> = Vec::new();
+        for id in tags.split(',') {
+            match id.parse::<i32>() {
+                Ok(id) => ids.push(id),
+                Err(_) => return Err(AppError::BadRequest)
+            }
+        }
+        ids.sort();
+        ids.dedup();
+        tag_ids = Some(ids);
+    }
+
+    let (sort_key, cursor_cast) = sort_key(sort);
+    let (direction, compare) = match order {
+        SortOrder::Asc => ("ASC", ">"),
+        SortOrder::Desc => ("DESC", "<")
+    };
+    let today = user_today("$12");
+    // `created_at` and `updated_at` are stored in UTC without a zone.
+    let created_from = user_timestamp("$4", "$12");
+    let created_to = user_timestamp("$5", "$12");
+    let updated_from = user_timestamp("$6", "$12");
+    let updated_to = user_timestamp("$7", "$12");
+    let sql = format!(r#"
+        SELECT {TASK_COLUMNS}
         FROM tasks
-        WHERE user_id = $1;
-    "#)
+        WHERE
+            id IN (SELECT task_id FROM task_access WHERE user_id = $1) AND
+            ($2::varchar IS NULL OR state    = $2) AND
+            ($3::varchar IS NULL OR priority = $3) AND
+            ($4::text IS NULL OR created_at >= {created_from} AT TIME ZONE 'UTC') AND
+            ($5::text IS NULL OR created_at <= {created_to} AT TIME ZONE 'UTC') AND
+            ($6::text IS NULL OR updated_at >= {updated_from} AT TIME ZONE 'UTC') AND
+            ($7::text IS NULL OR updated_at <= {updated_to} AT TIME ZONE 'UTC') AND
+            ($8::int IS NULL OR ({sort_key}, id) {compare} ($9::{cursor_cast}, $8)) AND
+            (
+                $11::varchar IS NULL OR
+                ($11 = 'overdue' AND NOT closed AND due_at < CURRENT_TIMESTAMP) OR
+                ($11 = 'today' AND NOT closed AND
+                    due_at >= {today} AND
+                    due_at <  {today} + INTERVAL '1 day') OR
+                ($11 = 'upcoming' AND NOT closed AND
+                    due_at >= {today} + INTERVAL '1 day')
+            ) AND
+            ($13::int IS NULL OR project_id = $13) AND
+            ($14::bool IS NOT TRUE OR project_id IS NULL) AND
+            (
+                $13::int IS NOT NULL OR
+                project_id IS NULL OR
+                project_id NOT IN (SELECT id FROM projects WHERE archived)
+            ) AND
+            (
+                $15::int[] IS NULL OR
+                ($16 = 'any' AND EXISTS (
+                    SELECT 1 FROM task_tags
+                    WHERE task_id = tasks.id AND tag_id = ANY($15)
+                )) OR
+                ($16 = 'all' AND cardinality($15) = (
+                    SELECT COUNT(*) FROM task_tags
+                    WHERE task_id = tasks.id AND tag_id = ANY($15)
+                ))
+            )
+        ORDER BY {sort_key} {direction}, id {direction}
+        LIMIT $10;
+    "#);
+    let result = sqlx::query_as::<_, Task>(&sql)
         .bind(user_id)
+        .bind(query_dto.state)
+        .bind(query_dto.priority)
+        .bind(query_dto.created_from)
+        .bind(query_dto.created_to)
+        .bind(query_dto.updated_from)
+        .bind(query_dto.updated_to)
+        .bind(cursor_id)
+        .bind(cursor_value)
+        .bind(limit + 1)
+        .bind(query_dto.view.map(view_name))
+        .bind(timezone)
+        .bind(query_dto.project_id)
+        .bind(query_dto.inbox)
+        .bind(tag_ids)
+        .bind(tag_mode_name(query_dto.tag_mode.unwrap_or(TagMode::Any)))
         .fetch_all(pool)
         .await;
     match result {
-        Ok(tasks) => return Ok(tasks),
+        Ok(mut tasks) => {
+            let mut next_cursor: Option<String> = None;
+            if tasks.len() as i64 > limit {
+                tasks.truncate(limit as usize);
+                next_cursor = tasks.last().map(|task| encode_cursor(task, sort));
+            }
+            return Ok(TaskPage { data: tasks, next_cursor });
+        }
         Err(e) => match e {
             sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            sqlx::Error::Database(db_err) => {
+                // 22xxx: the date filters or the cursor did not cast to a valid value.
+                if let Some(err_code) = db_err.code() {
+                    if err_code.starts_with("22") {
+                        return Err(AppError::BadRequest);
+                    }
+                }
+                error!("{:#?}", db_err);
+                return Err(AppError::InternalServer);
+            }
             other => {
                 error!("{:#?}", other);
                 return Err(AppError::InternalServer);
@@ -79,21 +403,191 @@ pub async fn get_all(
     }
 }
 
+/// Ranked full-text search over the user's task titles and bodies.
+pub async fn search(
+    search_dto: SearchDto,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<SearchResult>, AppError> {
+    let ts_query = match build_ts_query(&search_dto.q) {
+        Some(ts_query) => ts_query,
+        None => return Err(AppError::ValidationError(
+            "q: no searchable words".to_string()
+        ))
+    };
+    let title_highlight = highlight("title", "HighlightAll=true");
+    let body_highlight = highlight(
+        "COALESCE(body, '')",
+        "MaxFragments=2, MaxWords=20, MinWords=5"
+    );
+    let result = sqlx::query_as::<_, SearchResult>(&format!(r#"
+        SELECT
+            {TASK_COLUMNS},
+            ts_rank(search, query) as rank,
+            {title_highlight} as title_highlight,
+            {body_highlight} as body_highlight
+        FROM tasks, to_tsquery('simple', $2) query
+        WHERE
+            id IN (SELECT task_id FROM task_access WHERE user_id = $1) AND
+            search @@ query
+        ORDER BY rank DESC, id DESC
+        LIMIT $3
+        OFFSET $4;
+    "#))
+        .bind(user_id)
+        .bind(ts_query)
+        .bind(search_dto.limit.unwrap_or(DEFAULT_PAGE_LIMIT))
+        .bind(search_dto.offset.unwrap_or(0))
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(results) => return Ok(results),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// SQL for an HTML-escaped `ts_headline` of `column` with matches wrapped in
+/// `<mark>`. Control characters stand in for the tags until the text
+/// has been escaped.
+fn highlight(column: &str, options: &str) -> String {
+    return format!(r#"
+        replace(replace(
+            replace(replace(replace(
+                ts_headline(
+                    'simple',
+                    {column},
+                    query,
+                    'StartSel=' || chr(1) || ', StopSel=' || chr(2) || ', {options}'
+                ),
+            '&', '&amp;'), '<', '&lt;'), '>', '&gt;'),
+        chr(1), '<mark>'), chr(2), '</mark>')
+    "#);
+}
+
+/// Turns the user's query into `to_tsquery` syntax: plain words are AND-ed,
+/// `word*` becomes a prefix match and quoted words a phrase. Everything but
+/// letters, digits and `_` is dropped so the result is always valid.
+fn build_ts_query(q: &str) -> Option<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for (index, part) in q.split('"').enumerate() {
+        let words: Vec<String> = part
+            .split_whitespace()
+            .filter_map(|word| {
+                let prefix = word.ends_with('*');
+                let word: String = word
+                    .chars()
+                    .filter(|c| c.is_alphanumeric() || *c == '_')
+                    .collect::<String>()
+                    .to_lowercase();
+                if word.is_empty() {
+                    return None;
+                }
+                return Some(if prefix { format!("{}:*", word) } else { word });
+            })
+            .collect();
+        if words.is_empty() {
+            continue;
+        }
+        // Odd parts sit between a pair of quotes.
+        if index % 2 == 1 {
+            terms.push(format!("({})", words.join(" <-> ")));
+        } else {
+            terms.extend(words);
+        }
+    }
+    if terms.is_empty() {
+        return None;
+    }
+    return Some(terms.join(" & "));
+}
+
 pub async fn udpate(
     update_dto: UpdateDto,
     id: i32,
     user_id: i32,
+    version: Option<Vec<i32>>,
+    timezone: &str,
     pool: &Pool<Postgres>
 ) -> Result<Task, AppError> {
     let is_update: bool = update_dto.title.is_some() ||
-        update_dto.body.is_some()  ||
-        update_dto.state.is_some() ||
-        update_dto.priority.is_some() ;
+        update_dto.body.is_some()     ||
+        update_dto.state.is_some()    ||
+        update_dto.priority.is_some() ||
+        update_dto.start_at.is_some() ||
+        update_dto.due_at.is_some()   ||
+        update_dto.project_id.is_some() ||
+        update_dto.tag_ids.is_some() ;
     if !is_update {
         return Err(AppError::BadRequest);
     }
 
-    let result = sqlx::query_as::<_, Task>(r#"
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let task = apply_update(update_dto, id, user_id, version, timezone, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
+}
+
+/// Same as `udpate`, inside the caller's transaction.
+pub async fn apply_update(
+    update_dto: UpdateDto,
+    id: i32,
+    user_id: i32,
+    version: Option<Vec<i32>>,
+    timezone: &str,
+    conn: &mut PgConnection
+) -> Result<Task, AppError> {
+    services::share::check_task(id, user_id, Rank::Editor, &mut *conn).await?;
+    let mut version = version;
+    // The move comes first, so a new state is checked against the workflow
+    // of the project the task ends up in.
+    if let Some(project_id) = update_dto.project_id {
+        apply_move(MoveDto { project_id }, id, user_id, version, &mut *conn).await?;
+        version = None;
+    }
+    check_version(id, version, &mut *conn).await?;
+    let previous = sqlx::query_as::<_, (String, bool, Option<String>)>(r#"
+        SELECT
+            state,
+            closed,
+            (
+                SELECT name FROM workflow_states
+                WHERE workflow_id = task_workflow_id(user_id, project_id)
+                ORDER BY position
+                LIMIT 1
+            )
+        FROM tasks
+        WHERE id = $1
+        FOR UPDATE
+    "#)
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await;
+    let (previous_state, previous_closed, initial_state) = match previous {
+        Ok(previous) => previous,
+        Err(e) => return Err(task_error(e))
+    };
+    if let Some(state) = &update_dto.state {
+        if Some(state) != initial_state.as_ref() && *state != previous_state && update_dto.force != Some(true) {
+            check_blockers(id, &mut *conn).await?;
+        }
+    }
+
+    let start_at = user_timestamp("$7", "$9");
+    let due_at = user_timestamp("$8", "$9");
+    let result = sqlx::query_as::<_, Task>(&format!(r#"
         UPDATE tasks
         SET 
             title = CASE
@@ -112,58 +606,713 @@ pub async fn udpate(
                 WHEN LENGTH($4) > 0 THEN $4
                 ELSE priority
             END,
+            start_at = CASE
+                WHEN $10 THEN {start_at}
+                ELSE start_at
+            END,
+            due_at = CASE
+                WHEN $11 THEN {due_at}
+                ELSE due_at
+            END,
             updated_at = CASE
                 WHEN $5 THEN CURRENT_TIMESTAMP
                 ELSE updated_at
             END
-        WHERE
-            user_id = $6 AND
-            id      = $7
-        RETURNING
-            id,
-            user_id,
-            title,
-            body,
-            state,
-            priority,
-            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at, 
-            to_char(updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
-    "#)
+        WHERE id = $6
+        RETURNING {TASK_COLUMNS}
+    "#))
         .bind( & if update_dto.title.is_some() { update_dto.title.unwrap() } else { "".to_string() })
         .bind( & if update_dto.body.is_some() { update_dto.body.unwrap() } else { "".to_string() })
         .bind( & if update_dto.state.is_some() { update_dto.state.unwrap() } else { "".to_string() })
         .bind( & if update_dto.priority.is_some() { update_dto.priority.unwrap() } else { "".to_string() })
         .bind( true )
-        .bind(user_id)
         .bind(id)
-        .fetch_one(pool)
+        .bind(update_dto.start_at.clone().flatten())
+        .bind(update_dto.due_at.clone().flatten())
+        .bind(timezone)
+        .bind(update_dto.start_at.is_some())
+        .bind(update_dto.due_at.is_some())
+        .fetch_one(&mut *conn)
+        .await;
+    let mut task = match result {
+        Ok(task) => task,
+        Err(e) => return Err(task_error(e))
+    };
+    if let Some(tag_ids) = update_dto.tag_ids {
+        set_tags(task.id, &tag_ids, &mut *conn).await?;
+        task = find(task.id, user_id, &mut *conn).await?;
+    }
+    if task.series_id.is_some() && update_dto.scope == Some(Scope::Future) {
+        services::recurrence::apply_to_future(task.id, &mut *conn).await?;
+    }
+    if task.series_id.is_some() && task.closed && !previous_closed {
+        services::recurrence::spawn_next(task.id, &mut *conn).await?;
+    }
+    if task.closed && update_dto.complete_checklist == Some(true) {
+        let completed = sqlx::query(r#"
+            UPDATE checklist_items
+            SET
+                done       = TRUE,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE
+                task_id = $1 AND
+                NOT done
+        "#)
+            .bind(task.id)
+            .execute(&mut *conn)
+            .await;
+        if let Err(e) = completed {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+        task = find(task.id, user_id, &mut *conn).await?;
+    }
+    return Ok(task);
+}
+
+/// Moves the task into `project_id`, or to the inbox when it is `None`.
+/// Only owners can move a task, and the project must belong to the task's
+/// creator.
+pub async fn move_to_project(
+    move_dto: MoveDto,
+    id: i32,
+    user_id: i32,
+    version: Option<Vec<i32>>,
+    pool: &Pool<Postgres>
+) -> Result<Task, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let task = apply_move(move_dto, id, user_id, version, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
+}
+
+/// Refuses to move a task past the first state of its workflow while one of
+/// its blockers is open.
+async fn check_blockers(
+    id: i32,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT blockers.id FROM task_dependencies
+        JOIN tasks AS blockers ON blockers.id = task_dependencies.blocked_by_id
+        WHERE
+            task_dependencies.task_id = $1 AND
+            NOT blockers.closed AND
+            blockers.deleted_at IS NULL
+        ORDER BY blockers.id
+    "#)
+        .bind(id)
+        .fetch_all(conn)
+        .await;
+    match result {
+        Ok(blockers) => {
+            if blockers.is_empty() {
+                return Ok(());
+            }
+            let ids: Vec<String> = blockers.iter().map(|id| id.to_string()).collect();
+            return Err(AppError::ValidationError(format!(
+                "state: blocked by unfinished task(s) {}, set force to override",
+                ids.join(", ")
+            )));
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Same as `move_to_project`, inside the caller's transaction.
+pub async fn apply_move(
+    move_dto: MoveDto,
+    id: i32,
+    user_id: i32,
+    version: Option<Vec<i32>>,
+    conn: &mut PgConnection
+) -> Result<Task, AppError> {
+    services::share::check_task(id, user_id, Rank::Owner, &mut *conn).await?;
+    check_version(id, version, &mut *conn).await?;
+    if let Some(project_id) = move_dto.project_id {
+        let archived = sqlx::query_scalar::<_, bool>(r#"
+            SELECT archived FROM projects
+            WHERE id = $1
+        "#)
+            .bind(project_id)
+            .fetch_optional(&mut *conn)
+            .await;
+        match archived {
+            Ok(Some(true)) => return Err(archived_error()),
+            Ok(_) => {}
+            Err(e) => return Err(task_error(e))
+        }
+    }
+    let result = sqlx::query_as::<_, Task>(&format!(r#"
+        UPDATE tasks
+        SET
+            project_id = $1,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $2
+        RETURNING {TASK_COLUMNS}
+    "#))
+        .bind(move_dto.project_id)
+        .bind(id)
+        .fetch_one(&mut *conn)
         .await;
     match result {
         Ok(task) => return Ok(task),
+        Err(e) => return Err(task_error(e))
+    }
+}
+
+/// Gives the task a rank between its new neighbours. A missing neighbour
+/// is taken from the user's list, so `after_id` alone puts the task right
+/// after that one even when more tasks follow.
+pub async fn reorder(
+    reorder_dto: ReorderDto,
+    id: i32,
+    user_id: i32,
+    version: Option<Vec<i32>>,
+    pool: &Pool<Postgres>
+) -> Result<Task, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    services::share::check_task(id, user_id, Rank::Editor, &mut tx).await?;
+    check_version(id, version, &mut tx).await?;
+    let mut lower = match reorder_dto.after_id {
+        Some(after_id) => Some(neighbour_rank(after_id, id, user_id, "after_id", &mut tx).await?),
+        None => None
+    };
+    let mut upper = match reorder_dto.before_id {
+        Some(before_id) => Some(neighbour_rank(before_id, id, user_id, "before_id", &mut tx).await?),
+        None => None
+    };
+    if lower.is_none() || upper.is_none() {
+        let (compare, edge, known) = match &lower {
+            Some(rank) => (">", "MIN", rank.clone()),
+            None => ("<", "MAX", upper.clone().unwrap_or_default())
+        };
+        let result = sqlx::query_scalar::<_, Option<String>>(&format!(r#"
+            SELECT {edge}(rank)::text FROM tasks
+            WHERE
+                id IN (SELECT task_id FROM task_access WHERE user_id = $1) AND
+                id  <> $2 AND
+                rank {compare} $3::numeric
+        "#))
+            .bind(user_id)
+            .bind(id)
+            .bind(known)
+            .fetch_one(&mut *tx)
+            .await;
+        let adjacent = match result {
+            Ok(adjacent) => adjacent,
+            Err(e) => {
+                error!("{:#?}", e);
+                return Err(AppError::InternalServer);
+            }
+        };
+        if lower.is_none() {
+            lower = adjacent;
+        } else {
+            upper = adjacent;
+        }
+    }
+    let result = sqlx::query_as::<_, Task>(&format!(r#"
+        UPDATE tasks
+        SET rank = CASE
+            WHEN $1::numeric IS NULL THEN ceil($2::numeric) - 1
+            WHEN $2::numeric IS NULL THEN floor($1::numeric) + 1
+            ELSE ($1::numeric + $2::numeric) * 0.5
+        END
+        WHERE
+            id = $3 AND
+            ($1::numeric IS NULL OR $2::numeric IS NULL OR $1::numeric < $2::numeric)
+        RETURNING {TASK_COLUMNS}
+    "#))
+        .bind(lower)
+        .bind(upper)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await;
+    let task = match result {
+        Ok(task) => task,
         Err(e) => match e {
-            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            sqlx::Error::RowNotFound => return Err(AppError::ValidationError(
+                "after_id: must come before before_id".to_string()
+            )),
             other => {
                 error!("{:#?}", other);
                 return Err(AppError::InternalServer);
             }
         }
+    };
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
+}
+
+/// Rank of a neighbour the user can see, other than the moved task itself.
+async fn neighbour_rank(
+    neighbour_id: i32,
+    id: i32,
+    user_id: i32,
+    field: &str,
+    conn: &mut PgConnection
+) -> Result<String, AppError> {
+    let result = sqlx::query_scalar::<_, String>(r#"
+        SELECT rank::text FROM tasks
+        WHERE
+            id IN (SELECT task_id FROM task_access WHERE user_id = $1) AND
+            id  = $2 AND
+            id <> $3
+    "#)
+        .bind(user_id)
+        .bind(neighbour_id)
+        .bind(id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(rank) => return Ok(rank),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::ValidationError(
+                format!("{}: task NOT found", field)
+            )),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+/// Applies one operation to many tasks in a single transaction. Each task
+/// runs in its own savepoint, so a failing one is rolled back and reported
+/// while the others still go through.
+pub async fn bulk(
+    bulk_dto: BulkDto,
+    user_id: i32,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<BulkReport, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let mut results: Vec<BulkResult> = Vec::with_capacity(bulk_dto.ids.len());
+    for id in bulk_dto.ids {
+        let mut savepoint = match (&mut *tx).begin().await {
+            Ok(savepoint) => savepoint,
+            Err(e) => {
+                error!("{:#?}", e);
+                return Err(AppError::InternalServer);
+            }
+        };
+        let result = match bulk_dto.action {
+            BulkAction::SetState => apply_update(
+                UpdateDto { state: bulk_dto.state.clone(), ..Default::default() },
+                id,
+                user_id,
+                None,
+                timezone,
+                &mut savepoint
+            ).await.map(|_| ()),
+            BulkAction::SetPriority => apply_update(
+                UpdateDto { priority: bulk_dto.priority.clone(), ..Default::default() },
+                id,
+                user_id,
+                None,
+                timezone,
+                &mut savepoint
+            ).await.map(|_| ()),
+            BulkAction::Delete => apply_delete(id, user_id, None, &mut savepoint).await,
+            BulkAction::Move => apply_move(
+                MoveDto { project_id: bulk_dto.project_id },
+                id,
+                user_id,
+                None,
+                &mut savepoint
+            ).await.map(|_| ())
+        };
+        let finished = match result {
+            Ok(_) => savepoint.commit().await,
+            Err(_) => savepoint.rollback().await
+        };
+        if let Err(e) = finished {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+        match result {
+            Ok(_) => results.push(BulkResult { id, status: 200, error: None }),
+            Err(e) => {
+                let (status, message) = e.parts();
+                results.push(BulkResult { id, status: status.as_u16(), error: Some(message) });
+            }
+        }
+    }
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
     }
+    let succeeded = results.iter().filter(|result| result.error.is_none()).count();
+    return Ok(BulkReport {
+        succeeded,
+        failed: results.len() - succeeded,
+        results
+    });
+}
+
+pub async fn get_one(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Task, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    return find(id, user_id, &mut conn).await;
 }
 
+/// Fails with `PreconditionFailed` when none of the client's `If-Match`
+/// versions is the stored one. The row stays locked until the transaction
+/// ends, so the version can NOT move on between the check and the write.
+async fn check_version(
+    id: i32,
+    version: Option<Vec<i32>>,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let Some(versions) = version else {
+        return Ok(());
+    };
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT version FROM tasks
+        WHERE id = $1
+        FOR UPDATE
+    "#)
+        .bind(id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(stored) => {
+            if !versions.contains(&stored) {
+                return Err(AppError::PreconditionFailed);
+            }
+            return Ok(());
+        }
+        Err(e) => return Err(task_error(e))
+    }
+}
+
+pub async fn find(
+    id: i32,
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<Task, AppError> {
+    let result = sqlx::query_as::<_, Task>(&format!(r#"
+        SELECT {TASK_COLUMNS}
+        FROM tasks
+        WHERE
+            id IN (SELECT task_id FROM task_access WHERE user_id = $1) AND
+            id = $2
+    "#))
+        .bind(user_id)
+        .bind(id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(task) => return Ok(task),
+        Err(e) => return Err(task_error(e))
+    }
+}
+
+/// Replaces the tags of the task with `tag_ids`. Every tag must belong to
+/// the task's creator, otherwise nothing is attached.
+async fn set_tags(
+    task_id: i32,
+    tag_ids: &[i32],
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let mut tag_ids = tag_ids.to_vec();
+    tag_ids.sort();
+    tag_ids.dedup();
+    let deleted = sqlx::query(r#"
+        DELETE FROM task_tags
+        WHERE task_id = $1
+    "#)
+        .bind(task_id)
+        .execute(&mut *conn)
+        .await;
+    if let Err(e) = deleted {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    let inserted = sqlx::query(r#"
+        INSERT INTO task_tags (task_id, tag_id)
+        SELECT $1, id FROM tags
+        WHERE
+            user_id = (SELECT user_id FROM tasks WHERE id = $1) AND
+            id      = ANY($2)
+    "#)
+        .bind(task_id)
+        .bind(&tag_ids)
+        .execute(&mut *conn)
+        .await;
+    match inserted {
+        Ok(data) => {
+            if data.rows_affected() != tag_ids.len() as u64 {
+                return Err(AppError::ValidationError(
+                    "tag_ids: tag NOT found".to_string()
+                ));
+            }
+            return Ok(());
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Those of `tag_ids` that a task created by `user_id` in `project_id`
+/// can carry: tags belong to the task's owner, who is the project's creator
+/// in a shared project.
+pub async fn owner_tag_ids(
+    tag_ids: &[i32],
+    project_id: Option<i32>,
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<Vec<i32>, AppError> {
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT id FROM tags
+        WHERE
+            id      = ANY($1) AND
+            user_id = COALESCE((SELECT user_id FROM projects WHERE id = $2), $3)
+        ORDER BY id
+    "#)
+        .bind(tag_ids)
+        .bind(project_id)
+        .bind(user_id)
+        .fetch_all(conn)
+        .await;
+    match result {
+        Ok(tag_ids) => return Ok(tag_ids),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// SQL reading the text parameter `param` as a timestamp. Values carrying
+/// `Z` or an offset are taken as-is, naive ones are local to the user's `tz`.
+pub fn user_timestamp(param: &str, tz: &str) -> String {
+    return format!(
+        "(CASE WHEN {param} ~ 'T.*(Z|[+-]\\d{{2}}(:?\\d{{2}})?)$' \
+            THEN {param}::timestamptz \
+            ELSE {param}::timestamp AT TIME ZONE {tz} END)"
+    );
+}
+
+/// SQL for the start of the current day in the user's `tz`.
+fn user_today(tz: &str) -> String {
+    return format!("(date_trunc('day', CURRENT_TIMESTAMP AT TIME ZONE {tz}) AT TIME ZONE {tz})");
+}
+
+/// An archived project is closed: tasks can neither be added to it nor
+/// moved into it until it is unarchived.
+fn archived_error() -> AppError {
+    return AppError::ValidationError("project_id: project is archived".to_string());
+}
+
+fn task_error(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::RowNotFound => return AppError::NotFoundData,
+        sqlx::Error::Database(db_err) => {
+            if db_err.constraint() == Some("tasks_state_check") {
+                return AppError::ValidationError(
+                    "state: NOT in the workflow of the task".to_string()
+                );
+            }
+            if let Some(err_code) = db_err.code() {
+                if err_code == "23503" {
+                    return AppError::ValidationError(
+                        "project_id: project NOT found".to_string()
+                    );
+                }
+                if err_code == "23514" {
+                    return AppError::ValidationError(
+                        "start_at: must not be after due_at".to_string()
+                    );
+                }
+                if err_code.starts_with("22") {
+                    return AppError::ValidationError(
+                        "start_at, due_at: invalid datetime".to_string()
+                    );
+                }
+            }
+            error!("{:#?}", db_err);
+            return AppError::InternalServer;
+        }
+        other => {
+            error!("{:#?}", other);
+            return AppError::InternalServer;
+        }
+    }
+}
+
+fn tag_mode_name(tag_mode: TagMode) -> &'static str {
+    match tag_mode {
+        TagMode::Any => "any",
+        TagMode::All => "all"
+    }
+}
+
+fn view_name(view: View) -> &'static str {
+    match view {
+        View::Today => "today",
+        View::Upcoming => "upcoming",
+        View::Overdue => "overdue"
+    }
+}
+
+/// Returns the SQL expression used to order by `sort` and the type the
+/// cursor value must be cast to. Timestamps are truncated to seconds so the
+/// keyset matches the precision exposed in `Task`.
+fn sort_key(sort: SortField) -> (&'static str, &'static str) {
+    match sort {
+        SortField::CreatedAt => ("date_trunc('second', created_at)", "timestamp"),
+        SortField::UpdatedAt => ("date_trunc('second', updated_at)", "timestamp"),
+        SortField::Title => ("title", "varchar"),
+        SortField::State => (
+            "(SELECT position FROM workflow_states WHERE id = tasks.state_id)",
+            "int"
+        ),
+        SortField::Priority => (
+            "CASE priority WHEN 'LOW' THEN 1 WHEN 'MEDIUM' THEN 2 ELSE 3 END",
+            "int"
+        ),
+        SortField::DueAt => (
+            "date_trunc('second', COALESCE(due_at, 'infinity'::timestamptz))",
+            "timestamptz"
+        ),
+        SortField::Rank => ("rank", "numeric")
+    }
+}
+
+fn sort_name(sort: SortField) -> &'static str {
+    match sort {
+        SortField::CreatedAt => "created_at",
+        SortField::UpdatedAt => "updated_at",
+        SortField::Title => "title",
+        SortField::State => "state",
+        SortField::Priority => "priority",
+        SortField::DueAt => "due_at",
+        SortField::Rank => "rank"
+    }
+}
+
+fn sort_value(task: &Task, sort: SortField) -> String {
+    match sort {
+        SortField::CreatedAt => task.created_at.clone().unwrap_or_default(),
+        SortField::UpdatedAt => task.updated_at.clone().unwrap_or_default(),
+        SortField::Title => task.title.clone(),
+        SortField::State => task.state_position.to_string(),
+        SortField::Priority => match task.priority.as_str() {
+            "LOW" => "1",
+            "MEDIUM" => "2",
+            _ => "3"
+        }.to_string(),
+        SortField::DueAt => task.due_at.clone().unwrap_or("infinity".to_string()),
+        SortField::Rank => task.rank.clone()
+    }
+}
+
+/// The cursor is an opaque `sort|id|value` string, so a cursor issued for
+/// one sort field is rejected when replayed with another.
+fn encode_cursor(task: &Task, sort: SortField) -> String {
+    return URL_SAFE_NO_PAD.encode(
+        format!("{}|{}|{}", sort_name(sort), task.id, sort_value(task, sort))
+    );
+}
+
+fn decode_cursor(cursor: &str, sort: SortField) -> Option<(i32, String)> {
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let mut parts = decoded.splitn(3, '|');
+    if parts.next()? != sort_name(sort) {
+        return None;
+    }
+    let id = parts.next()?.parse::<i32>().ok()?;
+    let value = parts.next()?.to_string();
+    return Some((id, value));
+}
+
+/// Moves the task to the trash. It is purged for good once the retention
+/// period passes, see `services::trash`.
 pub async fn delete(
     id: i32,
     user_id: i32,
+    version: Option<Vec<i32>>,
     pool: &Pool<Postgres>
 ) -> Result<(), AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    apply_delete(id, user_id, version, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+/// Same as `delete`, inside the caller's transaction.
+pub async fn apply_delete(
+    id: i32,
+    user_id: i32,
+    version: Option<Vec<i32>>,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    services::share::check_task(id, user_id, Rank::Owner, &mut *conn).await?;
+    check_version(id, version, &mut *conn).await?;
     let result = sqlx::query(r#"
-        DELETE FROM tasks
+        UPDATE tasks
+        SET deleted_at = CURRENT_TIMESTAMP
         WHERE
-            id      = $1 AND
-            user_id = $2
+            id = $1 AND
+            deleted_at IS NULL
     "#)
         .bind(id)
-        .bind(user_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await;
     match result {
         Ok(data) => {
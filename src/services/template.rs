@@ -0,0 +1,345 @@
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+use validator::Validate;
+
+use crate::{
+    modules::{
+        task::{
+            self,
+            Task
+        },
+        template::{
+            CreateDto,
+            InstantiateDto,
+            Template,
+            UpdateDto
+        }
+    },
+    services,
+    error::AppError
+};
+
+const TEMPLATE_COLUMNS: &str = r#"
+    task_templates.id,
+    task_templates.user_id,
+    task_templates.name,
+    task_templates.title,
+    task_templates.body,
+    task_templates.priority,
+    COALESCE((
+        SELECT json_agg(
+            json_build_object('id', tags.id, 'name', tags.name, 'color', tags.color)
+            ORDER BY tags.name
+        )
+        FROM task_template_tags
+        JOIN tags ON tags.id = task_template_tags.tag_id
+        WHERE task_template_tags.template_id = task_templates.id
+    ), '[]') as tags,
+    task_templates.checklist,
+    to_char(task_templates.created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+    to_char(task_templates.updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+"#;
+
+pub async fn get_all(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Template>, AppError> {
+    let result = sqlx::query_as::<_, Template>(&format!(r#"
+        SELECT {TEMPLATE_COLUMNS}
+        FROM task_templates
+        WHERE user_id = $1
+        ORDER BY name, id
+    "#))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(templates) => return Ok(templates),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn get_one(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Template, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    return find(id, user_id, &mut conn).await;
+}
+
+pub async fn create(
+    create_dto: CreateDto,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Template, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        INSERT INTO task_templates (user_id, name, title, body, priority, checklist)
+        VALUES ( $1, $2, $3, $4, $5, $6 )
+        RETURNING id
+    "#)
+        .bind(user_id)
+        .bind(create_dto.name)
+        .bind(create_dto.title)
+        .bind(create_dto.body.unwrap_or_default())
+        .bind(create_dto.priority.unwrap_or("MEDIUM".to_string()))
+        .bind(create_dto.checklist.unwrap_or_default())
+        .fetch_one(&mut *tx)
+        .await;
+    let id = match result {
+        Ok(id) => id,
+        Err(e) => return Err(template_error(e))
+    };
+    if let Some(tag_ids) = create_dto.tag_ids {
+        set_tags(id, &tag_ids, &mut tx).await?;
+    }
+    let template = find(id, user_id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(template);
+}
+
+pub async fn update(
+    update_dto: UpdateDto,
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Template, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let result = sqlx::query(r#"
+        UPDATE task_templates
+        SET
+            name       = COALESCE($1, name),
+            title      = COALESCE($2, title),
+            body       = COALESCE($3, body),
+            priority   = COALESCE($4, priority),
+            checklist  = COALESCE($5, checklist),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            id      = $6 AND
+            user_id = $7
+    "#)
+        .bind(update_dto.name)
+        .bind(update_dto.title)
+        .bind(update_dto.body)
+        .bind(update_dto.priority)
+        .bind(update_dto.checklist)
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() == 0 {
+                return Err(AppError::NotFoundData);
+            }
+        }
+        Err(e) => return Err(template_error(e))
+    }
+    if let Some(tag_ids) = update_dto.tag_ids {
+        set_tags(id, &tag_ids, &mut tx).await?;
+    }
+    let template = find(id, user_id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(template);
+}
+
+pub async fn delete(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        DELETE FROM task_templates
+        WHERE
+            id      = $1 AND
+            user_id = $2
+    "#)
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Creates a task from the template through `apply_create` and its rules,
+/// then gives it the template's checklist. Tags the new task's owner does
+/// not have, in a project shared with the user, are left off.
+pub async fn instantiate(
+    instantiate_dto: InstantiateDto,
+    id: i32,
+    user_id: i32,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<Task, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let template = find(id, user_id, &mut tx).await?;
+    let template_tag_ids: Vec<i32> = template.tags.iter().map(|tag| tag.id).collect();
+    let tag_ids = services::task::owner_tag_ids(
+        &template_tag_ids,
+        instantiate_dto.project_id,
+        user_id,
+        &mut tx
+    ).await?;
+    let create_dto = task::CreateDto {
+        title: instantiate_dto.title.unwrap_or(template.title),
+        body: Some(template.body).filter(|body| !body.is_empty()),
+        state: instantiate_dto.state,
+        priority: Some(template.priority),
+        start_at: instantiate_dto.start_at,
+        due_at: instantiate_dto.due_at,
+        project_id: instantiate_dto.project_id,
+        tag_ids: Some(tag_ids),
+        recurrence: None
+    };
+    if let Err(e) = create_dto.validate() {
+        return Err(AppError::ValidationError(e.to_string()));
+    }
+    let task = services::task::apply_create(create_dto, user_id, timezone, &mut tx).await?;
+    let done = vec![false; template.checklist.len()];
+    services::checklist::add_items(task.id, &template.checklist, &done, &mut tx).await?;
+    let task = services::task::find(task.id, user_id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
+}
+
+async fn find(
+    id: i32,
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<Template, AppError> {
+    let result = sqlx::query_as::<_, Template>(&format!(r#"
+        SELECT {TEMPLATE_COLUMNS}
+        FROM task_templates
+        WHERE
+            id      = $1 AND
+            user_id = $2
+    "#))
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(template) => return Ok(template),
+        Err(e) => return Err(template_error(e))
+    }
+}
+
+/// Replaces the tags of the template with `tag_ids`, which must all be tags
+/// of the template's user.
+async fn set_tags(
+    id: i32,
+    tag_ids: &[i32],
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let mut tag_ids = tag_ids.to_vec();
+    tag_ids.sort();
+    tag_ids.dedup();
+    let deleted = sqlx::query(r#"
+        DELETE FROM task_template_tags
+        WHERE template_id = $1
+    "#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+    if let Err(e) = deleted {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    let inserted = sqlx::query(r#"
+        INSERT INTO task_template_tags (template_id, tag_id)
+        SELECT $1, id FROM tags
+        WHERE
+            user_id = (SELECT user_id FROM task_templates WHERE id = $1) AND
+            id      = ANY($2)
+    "#)
+        .bind(id)
+        .bind(&tag_ids)
+        .execute(&mut *conn)
+        .await;
+    match inserted {
+        Ok(data) => {
+            if data.rows_affected() != tag_ids.len() as u64 {
+                return Err(AppError::ValidationError(
+                    "tag_ids: tag NOT found".to_string()
+                ));
+            }
+            return Ok(());
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+fn template_error(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::RowNotFound => return AppError::NotFoundData,
+        sqlx::Error::Database(db_err) => {
+            if db_err.code().as_deref() == Some("23505") {
+                return AppError::ValidationError(
+                    "name: already used by another template".to_string()
+                );
+            }
+            error!("{:#?}", db_err);
+            return AppError::InternalServer;
+        }
+        other => {
+            error!("{:#?}", other);
+            return AppError::InternalServer;
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,511 @@
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        time_entry::{
+            CreateDto,
+            Group,
+            Report,
+            ReportQuery,
+            ReportRow,
+            StartDto,
+            TaskTime,
+            TimeEntry,
+            UpdateDto
+        },
+        share::Rank
+    },
+    services::{
+        self,
+        task::user_timestamp,
+        transfer::escape_cell
+    },
+    error::AppError
+};
+
+const TIME_ENTRY_COLUMNS: &str = r#"
+    time_entries.id,
+    time_entries.task_id,
+    time_entries.user_id,
+    users.username,
+    to_char(time_entries.started_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as started_at,
+    to_char(time_entries.ended_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as ended_at,
+    EXTRACT(EPOCH FROM COALESCE(time_entries.ended_at, CURRENT_TIMESTAMP) - time_entries.started_at)::BIGINT as seconds,
+    time_entries.note,
+    to_char(time_entries.created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+    to_char(time_entries.updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+"#;
+
+/// Entries of user `$1` started from day `$3` through day `$4` of
+/// timezone `$2`, with the seconds they count.
+const REPORT_ENTRIES: &str = r#"
+    SELECT
+        time_entries.task_id,
+        (time_entries.started_at AT TIME ZONE $2)::DATE as day,
+        EXTRACT(EPOCH FROM COALESCE(time_entries.ended_at, CURRENT_TIMESTAMP) - time_entries.started_at)::BIGINT as seconds
+    FROM time_entries
+    WHERE
+        time_entries.user_id     = $1 AND
+        time_entries.started_at >= $3::DATE::TIMESTAMP AT TIME ZONE $2 AND
+        time_entries.started_at <  ($4::DATE + 1)::TIMESTAMP AT TIME ZONE $2
+"#;
+
+pub async fn get_all(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<TaskTime, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Viewer, &mut conn).await?;
+    let result = sqlx::query_as::<_, TimeEntry>(&format!(r#"
+        SELECT {TIME_ENTRY_COLUMNS}
+        FROM time_entries
+        JOIN users ON users.id = time_entries.user_id
+        WHERE time_entries.task_id = $1
+        ORDER BY time_entries.started_at, time_entries.id
+    "#))
+        .bind(task_id)
+        .fetch_all(&mut *conn)
+        .await;
+    match result {
+        Ok(entries) => return Ok(TaskTime {
+            total_seconds: entries.iter().map(|entry| entry.seconds).sum(),
+            entries
+        }),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Starts a timer on the task. Editors can log time, and a user has at
+/// most one timer running, so a running one has to be stopped first.
+pub async fn start(
+    start_dto: StartDto,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<TimeEntry, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut conn).await?;
+    if let Some(running) = find_running(user_id, &mut conn).await? {
+        return Err(AppError::ValidationError(
+            format!("timer: already running on task {}", running.task_id)
+        ));
+    }
+    let result = sqlx::query_as::<_, TimeEntry>(&format!(r#"
+        WITH time_entries AS (
+            INSERT INTO time_entries (task_id, user_id, started_at, note)
+            VALUES ( $1, $2, CURRENT_TIMESTAMP, $3 )
+            RETURNING *
+        )
+        SELECT {TIME_ENTRY_COLUMNS}
+        FROM time_entries
+        JOIN users ON users.id = time_entries.user_id
+    "#))
+        .bind(task_id)
+        .bind(user_id)
+        .bind(start_dto.note.unwrap_or_default())
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(entry) => return Ok(entry),
+        Err(e) => return Err(time_entry_error(e))
+    }
+}
+
+/// Stops the user's timer on the task. Needs no access to the task, so a
+/// timer left running on a task that was since trashed or unshared can
+/// still be stopped.
+pub async fn stop(
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<TimeEntry, AppError> {
+    let result = sqlx::query_as::<_, TimeEntry>(&format!(r#"
+        WITH time_entries AS (
+            UPDATE time_entries
+            SET
+                ended_at   = CURRENT_TIMESTAMP,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE
+                task_id = $1 AND
+                user_id = $2 AND
+                ended_at IS NULL
+            RETURNING *
+        )
+        SELECT {TIME_ENTRY_COLUMNS}
+        FROM time_entries
+        JOIN users ON users.id = time_entries.user_id
+    "#))
+        .bind(task_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await;
+    match result {
+        Ok(entry) => return Ok(entry),
+        Err(e) => return Err(time_entry_error(e))
+    }
+}
+
+/// The user's running timer, on whichever task it is.
+pub async fn running(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<TimeEntry, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    match find_running(user_id, &mut conn).await? {
+        Some(entry) => return Ok(entry),
+        None => return Err(AppError::NotFoundData)
+    }
+}
+
+/// Logs time spent on the task after the fact.
+pub async fn create(
+    create_dto: CreateDto,
+    task_id: i32,
+    user_id: i32,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<TimeEntry, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_task(task_id, user_id, Rank::Editor, &mut conn).await?;
+    let started_at = user_timestamp("$3", "$6");
+    let ended_at = user_timestamp("$4", "$6");
+    let result = sqlx::query_as::<_, TimeEntry>(&format!(r#"
+        WITH time_entries AS (
+            INSERT INTO time_entries (task_id, user_id, started_at, ended_at, note)
+            VALUES ( $1, $2, {started_at}, {ended_at}, $5 )
+            RETURNING *
+        )
+        SELECT {TIME_ENTRY_COLUMNS}
+        FROM time_entries
+        JOIN users ON users.id = time_entries.user_id
+    "#))
+        .bind(task_id)
+        .bind(user_id)
+        .bind(create_dto.started_at)
+        .bind(create_dto.ended_at)
+        .bind(create_dto.note.unwrap_or_default())
+        .bind(timezone)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(entry) => return Ok(entry),
+        Err(e) => return Err(time_entry_error(e))
+    }
+}
+
+/// Only the user who logged an entry can change it. Setting `ended_at`
+/// on a running timer stops it then.
+pub async fn update(
+    update_dto: UpdateDto,
+    id: i32,
+    task_id: i32,
+    user_id: i32,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<TimeEntry, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let owner_id = find_owner(id, task_id, &mut conn).await?;
+    if owner_id != user_id {
+        return Err(AppError::Forbidden);
+    }
+    let started_at = user_timestamp("$1", "$4");
+    let ended_at = user_timestamp("$2", "$4");
+    let result = sqlx::query_as::<_, TimeEntry>(&format!(r#"
+        WITH time_entries AS (
+            UPDATE time_entries
+            SET
+                started_at = CASE WHEN $1::TEXT IS NULL THEN started_at ELSE {started_at} END,
+                ended_at   = CASE WHEN $2::TEXT IS NULL THEN ended_at ELSE {ended_at} END,
+                note       = COALESCE($3, note),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $5
+            RETURNING *
+        )
+        SELECT {TIME_ENTRY_COLUMNS}
+        FROM time_entries
+        JOIN users ON users.id = time_entries.user_id
+    "#))
+        .bind(update_dto.started_at)
+        .bind(update_dto.ended_at)
+        .bind(update_dto.note)
+        .bind(timezone)
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok(entry) => return Ok(entry),
+        Err(e) => return Err(time_entry_error(e))
+    }
+}
+
+/// Only the user who logged an entry can delete it.
+pub async fn delete(
+    id: i32,
+    task_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let owner_id = find_owner(id, task_id, &mut conn).await?;
+    if owner_id != user_id {
+        return Err(AppError::Forbidden);
+    }
+    let result = sqlx::query(r#"
+        DELETE FROM time_entries
+        WHERE id = $1
+    "#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() > 0 {
+                return Ok(());
+            }
+            return Err(AppError::NotFoundData);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// The user's time per day and project, or per day and tag, summed in SQL.
+/// Entries count with their task wherever it is now, trash included.
+pub async fn report(
+    report_query: ReportQuery,
+    user_id: i32,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<Report, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let today = services::stats::today(timezone, &mut conn).await?;
+    let (from, to) = services::stats::date_range(
+        report_query.from.as_deref(),
+        report_query.to.as_deref(),
+        today
+    )?;
+    let group = report_query.group.unwrap_or(Group::Project);
+    let (group_join, group_id, group_name) = match group {
+        Group::Project => (
+            "JOIN tasks ON tasks.id = entries.task_id
+            LEFT JOIN projects ON projects.id = tasks.project_id",
+            "projects.id",
+            "projects.name"
+        ),
+        Group::Tag => (
+            "LEFT JOIN task_tags ON task_tags.task_id = entries.task_id
+            LEFT JOIN tags ON tags.id = task_tags.tag_id",
+            "tags.id",
+            "tags.name"
+        )
+    };
+    let rows = sqlx::query_as::<_, ReportRow>(&format!(r#"
+        WITH entries AS ({REPORT_ENTRIES})
+        SELECT
+            to_char(entries.day, 'YYYY-MM-DD') as date,
+            {group_id} as id,
+            {group_name} as name,
+            SUM(entries.seconds)::BIGINT as seconds
+        FROM entries
+        {group_join}
+        GROUP BY entries.day, {group_id}, {group_name}
+        ORDER BY entries.day, {group_name} NULLS FIRST, {group_id}
+    "#))
+        .bind(user_id)
+        .bind(timezone)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&mut *conn)
+        .await;
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let total_seconds = sqlx::query_scalar::<_, i64>(&format!(r#"
+        WITH entries AS ({REPORT_ENTRIES})
+        SELECT COALESCE(SUM(entries.seconds), 0)::BIGINT
+        FROM entries
+    "#))
+        .bind(user_id)
+        .bind(timezone)
+        .bind(from)
+        .bind(to)
+        .fetch_one(&mut *conn)
+        .await;
+    let total_seconds = match total_seconds {
+        Ok(total_seconds) => total_seconds,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    return Ok(Report {
+        from: from.format("%Y-%m-%d").to_string(),
+        to: to.format("%Y-%m-%d").to_string(),
+        group,
+        total_seconds,
+        rows
+    });
+}
+
+/// The rows of a report as CSV, with the time in seconds and in hours.
+pub fn report_csv(report: &Report) -> String {
+    let group = match report.group {
+        Group::Project => "project",
+        Group::Tag => "tag"
+    };
+    let mut writer = csv::Writer::from_writer(vec![]);
+    let mut records = vec![vec![
+        "date".to_string(),
+        format!("{}_id", group),
+        group.to_string(),
+        "seconds".to_string(),
+        "hours".to_string()
+    ]];
+    for row in &report.rows {
+        records.push(vec![
+            row.date.clone(),
+            row.id.map(|id| id.to_string()).unwrap_or_default(),
+            escape_cell(row.name.as_deref().unwrap_or_default()),
+            row.seconds.to_string(),
+            format!("{:.2}", row.seconds as f64 / 3600.0)
+        ]);
+    }
+    for record in records {
+        if let Err(e) = writer.write_record(&record) {
+            error!("{:#?}", e);
+        }
+    }
+    let bytes = writer.into_inner().unwrap_or_default();
+    return String::from_utf8(bytes).unwrap_or_default();
+}
+
+async fn find_running(
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<Option<TimeEntry>, AppError> {
+    let result = sqlx::query_as::<_, TimeEntry>(&format!(r#"
+        SELECT {TIME_ENTRY_COLUMNS}
+        FROM time_entries
+        JOIN users ON users.id = time_entries.user_id
+        WHERE
+            time_entries.user_id = $1 AND
+            time_entries.ended_at IS NULL
+    "#))
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await;
+    match result {
+        Ok(entry) => return Ok(entry),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+async fn find_owner(
+    id: i32,
+    task_id: i32,
+    conn: &mut PgConnection
+) -> Result<i32, AppError> {
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        SELECT user_id FROM time_entries
+        WHERE
+            id      = $1 AND
+            task_id = $2
+    "#)
+        .bind(id)
+        .bind(task_id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(owner_id) => return Ok(owner_id),
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+}
+
+fn time_entry_error(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::RowNotFound => return AppError::NotFoundData,
+        sqlx::Error::Database(db_err) => {
+            if db_err.constraint() == Some("time_entries_range_check") {
+                return AppError::ValidationError(
+                    "ended_at: must NOT be before started_at".to_string()
+                );
+            }
+            // A concurrent start won the race for the one running timer.
+            if db_err.constraint() == Some("time_entries_running_idx") {
+                return AppError::ValidationError("timer: already running".to_string());
+            }
+            error!("{:#?}", db_err);
+            return AppError::InternalServer;
+        }
+        other => {
+            error!("{:#?}", other);
+            return AppError::InternalServer;
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,652 @@
+use futures_util::{
+    stream,
+    Stream
+};
+use std::collections::{
+    hash_map::Entry,
+    HashMap
+};
+
+use sqlx::{
+    Acquire,
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+use validator::Validate;
+
+use crate::{
+    modules::{
+        task::CreateDto,
+        transfer::{
+            CsvRow,
+            ExportRow,
+            Format,
+            ImportReport,
+            ImportResult,
+            TodoTxtRow,
+            MAX_IMPORT_ROWS
+        },
+        workflow::StateKind
+    },
+    services,
+    todotxt,
+    error::AppError
+};
+
+/// A row of an import file: its number and the task it describes.
+type ParsedRow = (usize, Result<ParsedTask, AppError>);
+
+/// A task read from an import file. The `+project` and `@context` names of
+/// a todo.txt line are only resolved once the task is valid, inside the
+/// savepoint of its row, so a rejected row leaves nothing behind.
+struct ParsedTask {
+    create_dto: CreateDto,
+    /// A completed todo.txt line.
+    done: bool,
+    project: Option<String>,
+    contexts: Vec<String>
+}
+
+/// Ids of the projects and tags a todo.txt import can use, by lowercased
+/// name, and the closed state of each workflow met so far. `created_*`
+/// hold the names created for the current row.
+struct TodoTxtNames {
+    projects: HashMap<String, i32>,
+    tags: HashMap<String, i32>,
+    closed_states: HashMap<Option<i32>, Option<String>>,
+    created_projects: Vec<String>,
+    created_tags: Vec<String>
+}
+
+/// Tasks read from the database per chunk of an export.
+const EXPORT_BATCH: i64 = 500;
+
+const CSV_HEADER: [&str; 10] = [
+    "id",
+    "title",
+    "body",
+    "state",
+    "priority",
+    "start_at",
+    "due_at",
+    "project_id",
+    "tag_ids",
+    "created_at"
+];
+
+struct ExportState {
+    format: Format,
+    user_id: i32,
+    timezone: String,
+    pool: Pool<Postgres>,
+    last_id: i32,
+    started: bool,
+    finished: bool
+}
+
+/// Streams the live tasks the user owns, in id order. Tasks are read in
+/// batches of `EXPORT_BATCH`, so large accounts are never held in memory.
+pub fn export(
+    format: Format,
+    user_id: i32,
+    timezone: String,
+    pool: Pool<Postgres>
+) -> impl Stream<Item = Result<String, std::io::Error>> + Send {
+    let state = ExportState {
+        format,
+        user_id,
+        timezone,
+        pool,
+        last_id: 0,
+        started: false,
+        finished: false
+    };
+    return stream::unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+        if state.format == Format::TodoTxt {
+            let rows = match todotxt_batch(state.user_id, &state.timezone, state.last_id, &state.pool).await {
+                Ok(rows) => rows,
+                Err(_) => {
+                    state.finished = true;
+                    return Some((Err(std::io::Error::other("export failed")), state));
+                }
+            };
+            let mut chunk = String::new();
+            for row in &rows {
+                chunk.push_str(&todotxt::format(row));
+                chunk.push('\n');
+                state.last_id = row.id;
+            }
+            state.finished = (rows.len() as i64) < EXPORT_BATCH;
+            return Some((Ok(chunk), state));
+        }
+        let rows = match export_batch(state.user_id, state.last_id, &state.pool).await {
+            Ok(rows) => rows,
+            Err(_) => {
+                state.finished = true;
+                return Some((Err(std::io::Error::other("export failed")), state));
+            }
+        };
+        let mut chunk = String::new();
+        if !state.started {
+            chunk.push_str(&match state.format {
+                Format::Csv => csv_line(&CSV_HEADER),
+                _ => "[".to_string()
+            });
+        }
+        for row in &rows {
+            match state.format {
+                Format::Csv => {
+                    let tag_ids: Vec<String> = row.tag_ids.iter().map(|id| id.to_string()).collect();
+                    chunk.push_str(&csv_line(&[
+                        &row.id.to_string(),
+                        &row.title,
+                        &row.body,
+                        &row.state,
+                        &row.priority,
+                        row.start_at.as_deref().unwrap_or(""),
+                        row.due_at.as_deref().unwrap_or(""),
+                        &row.project_id.map(|id| id.to_string()).unwrap_or_default(),
+                        &tag_ids.join(";"),
+                        &row.created_at
+                    ]));
+                }
+                _ => {
+                    if state.last_id > 0 {
+                        chunk.push(',');
+                    }
+                    chunk.push('\n');
+                    chunk.push_str(&serde_json::to_string(row).unwrap_or_default());
+                }
+            }
+            state.last_id = row.id;
+        }
+        if (rows.len() as i64) < EXPORT_BATCH {
+            state.finished = true;
+            if state.format == Format::Json {
+                chunk.push_str("\n]\n");
+            }
+        }
+        state.started = true;
+        return Some((Ok(chunk), state));
+    });
+}
+
+async fn export_batch(
+    user_id: i32,
+    after_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<ExportRow>, AppError> {
+    let result = sqlx::query_as::<_, ExportRow>(r#"
+        SELECT
+            id,
+            title,
+            COALESCE(body, '') as body,
+            state,
+            priority,
+            to_char(start_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as start_at,
+            to_char(due_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as due_at,
+            project_id,
+            ARRAY(
+                SELECT tag_id FROM task_tags
+                WHERE task_id = tasks.id
+                ORDER BY tag_id
+            ) as tag_ids,
+            to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at
+        FROM tasks
+        WHERE
+            id IN (
+                SELECT task_id FROM task_access
+                WHERE
+                    user_id = $1 AND
+                    rank    = 3
+            ) AND
+            id > $2
+        ORDER BY id
+        LIMIT $3
+    "#)
+        .bind(user_id)
+        .bind(after_id)
+        .bind(EXPORT_BATCH)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(rows) => return Ok(rows),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+async fn todotxt_batch(
+    user_id: i32,
+    timezone: &str,
+    after_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<TodoTxtRow>, AppError> {
+    let result = sqlx::query_as::<_, TodoTxtRow>(r#"
+        SELECT
+            id,
+            title,
+            closed,
+            priority,
+            (SELECT name FROM projects WHERE projects.id = tasks.project_id) as project,
+            ARRAY(
+                SELECT tags.name FROM task_tags
+                JOIN tags ON tags.id = task_tags.tag_id
+                WHERE task_tags.task_id = tasks.id
+                ORDER BY tags.name
+            ) as tags,
+            to_char(due_at at time zone $4, 'YYYY-MM-DD') as due_date,
+            to_char(start_at at time zone $4, 'YYYY-MM-DD') as start_date,
+            to_char((created_at at time zone 'UTC') at time zone $4, 'YYYY-MM-DD') as created_date,
+            to_char((COALESCE(completed_at, updated_at) at time zone 'UTC') at time zone $4, 'YYYY-MM-DD') as completed_date
+        FROM tasks
+        WHERE
+            id IN (
+                SELECT task_id FROM task_access
+                WHERE
+                    user_id = $1 AND
+                    rank    = 3
+            ) AND
+            id > $2
+        ORDER BY id
+        LIMIT $3
+    "#)
+        .bind(user_id)
+        .bind(after_id)
+        .bind(EXPORT_BATCH)
+        .bind(timezone)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(rows) => return Ok(rows),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+fn csv_line(fields: &[&str]) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    if let Err(e) = writer.write_record(fields.iter().map(|field| escape_cell(field))) {
+        error!("{:#?}", e);
+    }
+    let bytes = writer.into_inner().unwrap_or_default();
+    return String::from_utf8(bytes).unwrap_or_default();
+}
+
+/// Prefixes cells a spreadsheet would read as a formula with `'`. Cells
+/// already starting with `'` get one more, so `unescape_cell` gives every
+/// cell back unchanged.
+pub fn escape_cell(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\'']) {
+        return format!("'{}", value);
+    }
+    return value.to_string();
+}
+
+/// Drops the `'` added by `escape_cell`.
+fn unescape_cell(value: String) -> String {
+    match value.strip_prefix('\'') {
+        Some(rest) if rest.starts_with(['=', '+', '-', '@', '\'']) => return rest.to_string(),
+        _ => return value
+    }
+}
+
+/// Creates one task per row of `data`. Every row goes through the
+/// `CreateDto` rules and runs in its own savepoint, so bad rows are reported
+/// and skipped while the rest are kept. A dry run reports the same outcome
+/// and then rolls the whole import back.
+pub async fn import(
+    data: &str,
+    format: Format,
+    dry_run: bool,
+    user_id: i32,
+    timezone: &str,
+    pool: &Pool<Postgres>
+) -> Result<ImportReport, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let rows = match format {
+        Format::Json => parse_json(data)?,
+        Format::Csv => parse_csv(data)?,
+        Format::TodoTxt => parse_todotxt(data)
+    };
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(AppError::ValidationError(
+            format!("rows: max={}", MAX_IMPORT_ROWS)
+        ));
+    }
+    let mut names = match format {
+        Format::TodoTxt => Some(todotxt_names(user_id, &mut tx).await?),
+        _ => None
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let mut results: Vec<ImportResult> = Vec::with_capacity(rows.len());
+    for (row_number, row) in rows {
+        let parsed = match row.and_then(|parsed| {
+            parsed.create_dto.validate()
+                .map(|_| parsed)
+                .map_err(|e| AppError::ValidationError(e.to_string()))
+        }) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let (status, message) = e.parts();
+                results.push(ImportResult {
+                    row: row_number,
+                    status: status.as_u16(),
+                    task_id: None,
+                    error: Some(message)
+                });
+                continue;
+            }
+        };
+        let mut savepoint = match (&mut *tx).begin().await {
+            Ok(savepoint) => savepoint,
+            Err(e) => {
+                error!("{:#?}", e);
+                return Err(AppError::InternalServer);
+            }
+        };
+        let result = match names.as_mut() {
+            Some(names) => match resolve_names(parsed, names, user_id, &mut savepoint).await {
+                Ok(create_dto) => services::task::apply_create(create_dto, user_id, timezone, &mut savepoint).await,
+                Err(e) => Err(e)
+            },
+            None => services::task::apply_create(parsed.create_dto, user_id, timezone, &mut savepoint).await
+        };
+        let finished = match result {
+            Ok(_) => savepoint.commit().await,
+            Err(_) => savepoint.rollback().await
+        };
+        if let Some(names) = names.as_mut() {
+            let created_projects = std::mem::take(&mut names.created_projects);
+            let created_tags = std::mem::take(&mut names.created_tags);
+            if result.is_err() {
+                for name in created_projects {
+                    names.projects.remove(&name);
+                }
+                for name in created_tags {
+                    names.tags.remove(&name);
+                }
+            }
+        }
+        if let Err(e) = finished {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+        match result {
+            Ok(task) => results.push(ImportResult {
+                row: row_number,
+                status: 201,
+                task_id: Some(task.id),
+                error: None
+            }),
+            Err(e) => {
+                let (status, message) = e.parts();
+                results.push(ImportResult {
+                    row: row_number,
+                    status: status.as_u16(),
+                    task_id: None,
+                    error: Some(message)
+                });
+            }
+        }
+    }
+    let finished = if dry_run {
+        tx.rollback().await
+    } else {
+        tx.commit().await
+    };
+    if let Err(e) = finished {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    let succeeded = results.iter().filter(|result| result.error.is_none()).count();
+    return Ok(ImportReport {
+        dry_run,
+        succeeded,
+        failed: results.len() - succeeded,
+        results
+    });
+}
+
+/// A JSON array of objects shaped like `CreateDto`. Each object is read on
+/// its own, so a wrong field only fails its row.
+fn parse_json(data: &str) -> Result<Vec<ParsedRow>, AppError> {
+    let values = match serde_json::from_str::<Vec<serde_json::Value>>(data) {
+        Ok(values) => values,
+        Err(e) => return Err(AppError::ValidationError(format!("body: {}", e)))
+    };
+    return Ok(values.into_iter()
+        .enumerate()
+        .map(|(index, value)| (
+            index + 1,
+            serde_json::from_value::<CreateDto>(value)
+                .map(parsed_task)
+                .map_err(|e| AppError::ValidationError(e.to_string()))
+        ))
+        .collect());
+}
+
+/// CSV with a header row naming the `CreateDto` fields. Unknown columns,
+/// like the `id` and `created_at` of an export, are ignored.
+fn parse_csv(data: &str) -> Result<Vec<ParsedRow>, AppError> {
+    let mut reader = csv::Reader::from_reader(data.as_bytes());
+    if let Err(e) = reader.headers() {
+        return Err(AppError::ValidationError(format!("body: {}", e)));
+    }
+    return Ok(reader.deserialize::<CsvRow>()
+        .enumerate()
+        .map(|(index, row)| (index + 1, csv_row(row).map(parsed_task)))
+        .collect());
+}
+
+fn csv_row(row: Result<CsvRow, csv::Error>) -> Result<CreateDto, AppError> {
+    let row = row.map_err(|e| AppError::ValidationError(e.to_string()))?;
+    let tag_ids = match row.tag_ids {
+        Some(tag_ids) => Some(
+            tag_ids.split(';')
+                .map(|id| id.trim())
+                .filter(|id| !id.is_empty())
+                .map(|id| id.parse::<i32>())
+                .collect::<Result<Vec<i32>, _>>()
+                .map_err(|_| AppError::ValidationError(
+                    "tag_ids: must be ids separated by ';'".to_string()
+                ))?
+        ),
+        None => None
+    };
+    return Ok(CreateDto {
+        title: unescape_cell(row.title),
+        body: row.body.map(unescape_cell),
+        state: row.state.map(unescape_cell),
+        priority: row.priority,
+        start_at: row.start_at,
+        due_at: row.due_at,
+        project_id: row.project_id,
+        tag_ids,
+        recurrence: None
+    });
+}
+
+fn parsed_task(create_dto: CreateDto) -> ParsedTask {
+    return ParsedTask {
+        create_dto,
+        done: false,
+        project: None,
+        contexts: Vec::new()
+    };
+}
+
+/// One task per non-empty line, its `+project` and `@context` resolved by
+/// `resolve_names` when it is imported.
+fn parse_todotxt(data: &str) -> Vec<ParsedRow> {
+    return data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| (index + 1, todotxt_row(line)))
+        .collect();
+}
+
+fn todotxt_row(line: &str) -> Result<ParsedTask, AppError> {
+    let line = todotxt::parse(line).map_err(AppError::ValidationError)?;
+    if line.contexts.len() > 50 {
+        return Err(AppError::ValidationError("tag_ids: max=50".to_string()));
+    }
+    return Ok(ParsedTask {
+        create_dto: CreateDto {
+            title: line.title,
+            body: None,
+            state: None,
+            priority: line.priority.map(|letter| todotxt::priority(letter).to_string()),
+            start_at: line.threshold,
+            due_at: line.due,
+            project_id: None,
+            tag_ids: None,
+            recurrence: None
+        },
+        done: line.done,
+        project: line.project,
+        contexts: line.contexts
+    });
+}
+
+/// The projects the user can add tasks to and the user's own tags, by name
+/// ignoring case.
+async fn todotxt_names(
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<TodoTxtNames, AppError> {
+    let projects = name_ids(r#"
+        SELECT name, id FROM projects
+        WHERE id IN (
+            SELECT project_id FROM project_access
+            WHERE
+                user_id = $1 AND
+                rank   >= 2
+        )
+        ORDER BY id
+    "#, user_id, &mut *conn).await?;
+    let tags = name_ids(r#"
+        SELECT name, id FROM tags
+        WHERE user_id = $1
+        ORDER BY id
+    "#, user_id, &mut *conn).await?;
+    return Ok(TodoTxtNames {
+        projects,
+        tags,
+        closed_states: HashMap::new(),
+        created_projects: Vec::new(),
+        created_tags: Vec::new()
+    });
+}
+
+/// The task of a todo.txt line with its project and tags. Names matching
+/// none of `names` become new projects and tags of the user, recorded in
+/// `names.created_*`. Completed lines take the first closed state of their
+/// workflow.
+async fn resolve_names(
+    parsed: ParsedTask,
+    names: &mut TodoTxtNames,
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<CreateDto, AppError> {
+    let mut create_dto = parsed.create_dto;
+    if let Some(name) = &parsed.project {
+        let key = name.to_lowercase();
+        if let Entry::Vacant(entry) = names.projects.entry(key.clone()) {
+            entry.insert(create_name(r#"
+                INSERT INTO projects (user_id, name)
+                VALUES ( $1, $2 )
+                RETURNING id
+            "#, user_id, name, &mut *conn).await?);
+            names.created_projects.push(key.clone());
+        }
+        create_dto.project_id = Some(names.projects[&key]);
+    }
+    let mut tag_ids = Vec::with_capacity(parsed.contexts.len());
+    for name in &parsed.contexts {
+        let key = name.to_lowercase();
+        if let Entry::Vacant(entry) = names.tags.entry(key.clone()) {
+            entry.insert(create_name(r#"
+                INSERT INTO tags (user_id, name)
+                VALUES ( $1, $2 )
+                RETURNING id
+            "#, user_id, name, &mut *conn).await?);
+            names.created_tags.push(key.clone());
+        }
+        tag_ids.push(names.tags[&key]);
+    }
+    if !tag_ids.is_empty() {
+        create_dto.tag_ids = Some(tag_ids);
+    }
+    if parsed.done {
+        if let Entry::Vacant(entry) = names.closed_states.entry(create_dto.project_id) {
+            let states = services::workflow::states_for(user_id, create_dto.project_id, &mut *conn).await?;
+            entry.insert(
+                services::workflow::pick(&states, StateKind::Closed).map(|state| state.name.clone())
+            );
+        }
+        create_dto.state = names.closed_states[&create_dto.project_id].clone();
+    }
+    return Ok(create_dto);
+}
+
+async fn create_name(
+    query: &str,
+    user_id: i32,
+    name: &str,
+    conn: &mut PgConnection
+) -> Result<i32, AppError> {
+    let result = sqlx::query_scalar::<_, i32>(query)
+        .bind(user_id)
+        .bind(name)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(id) => return Ok(id),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// Maps the lower-cased todo.txt token of each name to its id; the first
+/// row wins when two names give the same token.
+async fn name_ids(
+    query: &str,
+    user_id: i32,
+    conn: &mut PgConnection
+) -> Result<HashMap<String, i32>, AppError> {
+    let result = sqlx::query_as::<_, (String, i32)>(query)
+        .bind(user_id)
+        .fetch_all(conn)
+        .await;
+    match result {
+        Ok(rows) => {
+            let mut ids = HashMap::new();
+            for (name, id) in rows {
+                ids.entry(todotxt::token(&name).to_lowercase()).or_insert(id);
+            }
+            return Ok(ids);
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,181 @@
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        share::Rank,
+        task::Task
+    },
+    services::{
+        self,
+        task::TASK_COLUMNS
+    },
+    error::AppError
+};
+
+/// Trashed tasks the user can restore, most recently deleted first. Like
+/// deleting, restoring needs the owner rank (3).
+pub async fn get_all(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Task>, AppError> {
+    let result = sqlx::query_as::<_, Task>(&format!(r#"
+        SELECT {TASK_COLUMNS}
+        FROM tasks
+        WHERE id IN (
+            SELECT task_id FROM trash_access
+            WHERE
+                user_id = $1 AND
+                rank    = 3
+        )
+        ORDER BY deleted_at DESC, id DESC;
+    "#))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(tasks) => return Ok(tasks),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn restore(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Task, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    services::share::check_trash(id, user_id, Rank::Owner, &mut tx).await?;
+    let result = sqlx::query_as::<_, Task>(&format!(r#"
+        UPDATE tasks
+        SET
+            deleted_at = NULL,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            id = $1 AND
+            deleted_at IS NOT NULL
+        RETURNING {TASK_COLUMNS}
+    "#))
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await;
+    let task = match result {
+        Ok(task) => task,
+        Err(e) => match e {
+            sqlx::Error::RowNotFound => return Err(AppError::NotFoundData),
+            other => {
+                error!("{:#?}", other);
+                return Err(AppError::InternalServer);
+            }
+        }
+    };
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(task);
+}
+
+/// Permanently deletes every task the user owns in the trash and returns
+/// how many were removed.
+pub async fn empty(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<u64, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let (purged, storage_keys) = purge(r#"
+        id IN (
+            SELECT task_id FROM trash_access
+            WHERE
+                user_id = $1 AND
+                rank    = 3
+        )
+    "#, user_id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    services::attachment::remove_blobs(storage_keys).await;
+    return Ok(purged);
+}
+
+/// Permanently deletes tasks that have been in the trash for longer than
+/// `retention_days`. Runs periodically in the background.
+pub async fn purge_expired(
+    retention_days: i32,
+    pool: &Pool<Postgres>
+) -> Result<u64, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let (purged, storage_keys) = purge(r#"
+        deleted_at < CURRENT_TIMESTAMP - make_interval(days => $1)
+    "#, retention_days, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    services::attachment::remove_blobs(storage_keys).await;
+    return Ok(purged);
+}
+
+/// Hard deletes the trashed tasks matching `condition`, which takes a
+/// single `$1` parameter. Returns how many were deleted and the keys of
+/// their attachment blobs, read in the same statement before the cascade
+/// drops the rows; the caller removes the blobs once it has committed.
+async fn purge(
+    condition: &str,
+    param: i32,
+    conn: &mut PgConnection
+) -> Result<(u64, Vec<String>), AppError> {
+    let result = sqlx::query_as::<_, (i64, Vec<String>)>(&format!(r#"
+        WITH purged AS (
+            DELETE FROM tasks
+            WHERE
+                deleted_at IS NOT NULL AND
+                {condition}
+            RETURNING id
+        )
+        SELECT
+            (SELECT COUNT(*) FROM purged),
+            ARRAY(
+                SELECT storage_key FROM attachments
+                WHERE task_id IN (SELECT id FROM purged)
+            )
+    "#))
+        .bind(param)
+        .fetch_one(&mut *conn)
+        .await;
+    match result {
+        Ok((purged, storage_keys)) => return Ok((purged as u64, storage_keys)),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
\ No newline at end of file
@@ -41,6 +41,7 @@ pub async fn create(
                 name, 
                 email, 
                 username, 
+                timezone,
                 to_char(create_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as create_at, 
                 to_char(update_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as update_at
         "#
@@ -83,6 +84,7 @@ pub async fn login(
                 name, 
                 email, 
                 username, 
+                timezone,
                 password,
                 to_char(create_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as create_at, 
                 to_char(update_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as update_at
@@ -124,6 +126,7 @@ pub async fn update_information(
     let mut _email: String = user.email.clone();
     let mut _name: String = user.name.clone();
     let mut _username: String = user.username.clone();
+    let mut _timezone: String = user.timezone.clone();
 
     if update_info_dto.email.is_some() {
         _email = update_info_dto.email.unwrap();
@@ -134,27 +137,55 @@ pub async fn update_information(
     if update_info_dto.username.is_some() {
         _username = update_info_dto.username.unwrap();
     }
+    if update_info_dto.timezone.is_some() {
+        _timezone = update_info_dto.timezone.unwrap();
+    }
 
     if  _name     == user.name  &&
         _email    == user.email &&
-        _username == user.username {
+        _username == user.username &&
+        _timezone == user.timezone {
         return Err(AppError::BadRequest);
     }
 
+    if _timezone != user.timezone {
+        let known = sqlx::query_scalar::<_, bool>(r#"
+            SELECT EXISTS (
+                SELECT 1 FROM pg_timezone_names
+                WHERE name = $1
+            )
+        "#)
+            .bind(&_timezone)
+            .fetch_one(pool)
+            .await;
+        match known {
+            Ok(true) => {},
+            Ok(false) => return Err(AppError::ValidationError(
+                format!("timezone: unknown timezone '{}'", _timezone)
+            )),
+            Err(e) => {
+                error!("{:#?}", e);
+                return Err(AppError::InternalServer);
+            }
+        }
+    }
+
     let user = sqlx::query_as::<_, User>(r#"
         UPDATE users
         SET 
             email     = $1,
             name      = $2,
             username  = $3,
+            timezone  = $4,
             update_at = CURRENT_TIMESTAMP
         WHERE
-            id = $4
+            id = $5
         RETURNING 
             id, 
             name, 
             email, 
             username,
+            timezone,
             password,
             to_char(create_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as create_at, 
             to_char(update_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as update_at
@@ -162,6 +193,7 @@ pub async fn update_information(
         .bind(_email)
         .bind(_name)
         .bind(_username)
+        .bind(_timezone)
         .bind(user.id)
         .fetch_one(pool)
         .await;
@@ -0,0 +1,557 @@
+use sqlx::{
+    PgConnection,
+    Pool,
+    Postgres
+};
+use tracing::error;
+
+use crate::{
+    modules::{
+        share::Rank,
+        workflow::{
+            CreateDto,
+            SetDto,
+            StateDto,
+            StateKind,
+            UpdateDto,
+            Workflow,
+            WorkflowState
+        }
+    },
+    services,
+    error::AppError
+};
+
+const WORKFLOW_COLUMNS: &str = r#"
+    workflows.id,
+    workflows.user_id,
+    workflows.name,
+    workflows.is_default,
+    COALESCE((
+        SELECT json_agg(
+            json_build_object(
+                'id', workflow_states.id,
+                'name', workflow_states.name,
+                'position', workflow_states.position,
+                'closed', workflow_states.closed
+            )
+            ORDER BY workflow_states.position
+        )
+        FROM workflow_states
+        WHERE workflow_states.workflow_id = workflows.id
+    ), '[]') as states,
+    to_char(workflows.created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+    to_char(workflows.updated_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as updated_at
+"#;
+
+pub async fn get_all(
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Workflow>, AppError> {
+    let result = sqlx::query_as::<_, Workflow>(&format!(r#"
+        SELECT {WORKFLOW_COLUMNS}
+        FROM workflows
+        WHERE user_id = $1
+        ORDER BY is_default DESC, name, id
+    "#))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await;
+    match result {
+        Ok(workflows) => return Ok(workflows),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+pub async fn create(
+    create_dto: CreateDto,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Workflow, AppError> {
+    if create_dto.states.iter().any(|state| state.id.is_some()) {
+        return Err(AppError::ValidationError(
+            "states: a new workflow has no state ids".to_string()
+        ));
+    }
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    let result = sqlx::query_scalar::<_, i32>(r#"
+        INSERT INTO workflows (user_id, name)
+        VALUES ( $1, $2 )
+        RETURNING id
+    "#)
+        .bind(user_id)
+        .bind(create_dto.name)
+        .fetch_one(&mut *tx)
+        .await;
+    let id = match result {
+        Ok(id) => id,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    save_states(id, &create_dto.states, &mut tx).await?;
+    let workflow = find(id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(workflow);
+}
+
+/// Renames the workflow and replaces its states. Tasks follow the renamed
+/// states, and those of a removed state move to the first remaining state
+/// that is as closed as it was.
+pub async fn update(
+    update_dto: UpdateDto,
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Workflow, AppError> {
+    if update_dto.name.is_none() && update_dto.states.is_none() {
+        return Err(AppError::BadRequest);
+    }
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let result = sqlx::query(r#"
+        UPDATE workflows
+        SET
+            name       = COALESCE($1, name),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            id      = $2 AND
+            user_id = $3
+    "#)
+        .bind(update_dto.name)
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await;
+    match result {
+        Ok(data) => {
+            if data.rows_affected() == 0 {
+                return Err(AppError::NotFoundData);
+            }
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+    if let Some(states) = update_dto.states {
+        save_states(id, &states, &mut tx).await?;
+    }
+    let workflow = find(id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(workflow);
+}
+
+/// Deletes a workflow other than the default one. Its projects and their
+/// tasks fall back to the default workflow.
+pub async fn delete(
+    id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    let is_default = sqlx::query_scalar::<_, bool>(r#"
+        SELECT is_default FROM workflows
+        WHERE
+            id      = $1 AND
+            user_id = $2
+        FOR UPDATE
+    "#)
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await;
+    match is_default {
+        Ok(false) => {}
+        Ok(true) => return Err(AppError::ValidationError(
+            "id: the default workflow can NOT be deleted".to_string()
+        )),
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::NotFoundData),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+    let projects = sqlx::query_scalar::<_, i32>(r#"
+        UPDATE projects
+        SET
+            workflow_id = NULL,
+            updated_at  = CURRENT_TIMESTAMP
+        WHERE workflow_id = $1
+        RETURNING id
+    "#)
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await;
+    let projects = match projects {
+        Ok(projects) => projects,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    for project_id in projects {
+        remap_project(project_id, &mut tx).await?;
+    }
+    let result = sqlx::query(r#"
+        DELETE FROM workflows
+        WHERE id = $1
+    "#)
+        .bind(id)
+        .execute(&mut *tx)
+        .await;
+    if let Err(e) = result {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+/// The workflow the tasks of the project follow. Members can read it even
+/// though it belongs to the project owner.
+pub async fn get_for_project(
+    project_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Workflow, AppError> {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::share::check_project(project_id, user_id, Rank::Viewer, &mut conn).await?;
+    return find_for_project(project_id, &mut conn).await;
+}
+
+/// Switches the project to another workflow of its owner. Tasks keep their
+/// state when the new workflow has one of the same name.
+pub async fn set_for_project(
+    set_dto: SetDto,
+    project_id: i32,
+    user_id: i32,
+    pool: &Pool<Postgres>
+) -> Result<Workflow, AppError> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    services::history::set_actor(user_id, &mut tx).await?;
+    services::share::check_project(project_id, user_id, Rank::Owner, &mut tx).await?;
+    let result = sqlx::query(r#"
+        UPDATE projects
+        SET
+            workflow_id = $1,
+            updated_at  = CURRENT_TIMESTAMP
+        WHERE id = $2
+    "#)
+        .bind(set_dto.workflow_id)
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await;
+    if let Err(e) = result {
+        if let sqlx::Error::Database(db_err) = &e {
+            if db_err.code().as_deref() == Some("23503") {
+                return Err(AppError::ValidationError(
+                    "workflow_id: workflow NOT found".to_string()
+                ));
+            }
+        }
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    remap_project(project_id, &mut tx).await?;
+    let workflow = find_for_project(project_id, &mut tx).await?;
+    if let Err(e) = tx.commit().await {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(workflow);
+}
+
+/// States of the workflow a task of `owner_id` in `project_id` follows,
+/// in order.
+pub async fn states_for(
+    owner_id: i32,
+    project_id: Option<i32>,
+    conn: &mut PgConnection
+) -> Result<Vec<WorkflowState>, AppError> {
+    let result = sqlx::query_as::<_, WorkflowState>(r#"
+        SELECT id, name, position, closed
+        FROM workflow_states
+        WHERE workflow_id = task_workflow_id($1, $2)
+        ORDER BY position
+    "#)
+        .bind(owner_id)
+        .bind(project_id)
+        .fetch_all(conn)
+        .await;
+    match result {
+        Ok(states) => return Ok(states),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+/// The first state of `states` of the given kind.
+pub fn pick(states: &[WorkflowState], kind: StateKind) -> Option<&WorkflowState> {
+    match kind {
+        StateKind::Initial => return states.first(),
+        StateKind::Active => return states.iter()
+            .skip(1)
+            .find(|state| !state.closed)
+            .or(states.first()),
+        StateKind::Closed => return states.iter().find(|state| state.closed)
+    }
+}
+
+pub fn kind(states: &[WorkflowState], name: &str) -> StateKind {
+    match states.iter().position(|state| state.name == name) {
+        Some(index) if states[index].closed => return StateKind::Closed,
+        Some(index) if index > 0 => return StateKind::Active,
+        _ => return StateKind::Initial
+    }
+}
+
+/// Writes `states` as the ordered states of workflow `id`, dropping the
+/// ones left out once their tasks have moved on.
+async fn save_states(
+    id: i32,
+    states: &[StateDto],
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let kept: Vec<i32> = states.iter().filter_map(|state| state.id).collect();
+    let found = sqlx::query_scalar::<_, i64>(r#"
+        SELECT COUNT(*) FROM workflow_states
+        WHERE
+            workflow_id = $1 AND
+            id          = ANY($2)
+    "#)
+        .bind(id)
+        .bind(&kept)
+        .fetch_one(&mut *conn)
+        .await;
+    match found {
+        Ok(found) => {
+            if found != kept.len() as i64 {
+                return Err(AppError::ValidationError(
+                    "states: state NOT found in the workflow".to_string()
+                ));
+            }
+        }
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+    // Removed states give up their name and position first, so the states
+    // of the new list can take them over.
+    let removed = sqlx::query_scalar::<_, i32>(r#"
+        UPDATE workflow_states
+        SET
+            name     = '#' || id,
+            position = -id
+        WHERE
+            workflow_id = $1 AND
+            id         <> ALL($2)
+        RETURNING id
+    "#)
+        .bind(id)
+        .bind(&kept)
+        .fetch_all(&mut *conn)
+        .await;
+    let removed = match removed {
+        Ok(removed) => removed,
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    };
+    for (position, state) in states.iter().enumerate() {
+        let result = sqlx::query(r#"
+            WITH updated AS (
+                UPDATE workflow_states
+                SET
+                    name     = $3,
+                    position = $4,
+                    closed   = $5
+                WHERE
+                    workflow_id = $1 AND
+                    id          = $2
+                RETURNING id
+            )
+            INSERT INTO workflow_states (workflow_id, name, position, closed)
+            SELECT $1, $3, $4, $5
+            WHERE $2::INT IS NULL
+        "#)
+            .bind(id)
+            .bind(state.id)
+            .bind(&state.name)
+            .bind(position as i32)
+            .bind(state.closed.unwrap_or(false))
+            .execute(&mut *conn)
+            .await;
+        if let Err(e) = result {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+    let moved = sqlx::query(r#"
+        UPDATE tasks
+        SET
+            state = CASE
+                WHEN tasks.state_id = ANY($2) THEN (
+                    SELECT remaining.name FROM workflow_states AS remaining
+                    WHERE
+                        remaining.workflow_id = $1 AND
+                        remaining.id         <> ALL($2)
+                    ORDER BY remaining.closed <> tasks.closed, remaining.position
+                    LIMIT 1
+                )
+                ELSE workflow_states.name
+            END,
+            updated_at = CURRENT_TIMESTAMP
+        FROM workflow_states
+        WHERE
+            workflow_states.id          = tasks.state_id AND
+            workflow_states.workflow_id = $1 AND (
+                tasks.state_id = ANY($2) OR
+                tasks.state   <> workflow_states.name OR
+                tasks.closed  <> workflow_states.closed
+            )
+    "#)
+        .bind(id)
+        .bind(&removed)
+        .execute(&mut *conn)
+        .await;
+    if let Err(e) = moved {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    let deleted = sqlx::query(r#"
+        DELETE FROM workflow_states
+        WHERE id = ANY($1)
+    "#)
+        .bind(&removed)
+        .execute(&mut *conn)
+        .await;
+    if let Err(e) = deleted {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+/// Puts the tasks of the project, trashed ones included, on a state of the
+/// workflow it now follows.
+async fn remap_project(
+    project_id: i32,
+    conn: &mut PgConnection
+) -> Result<(), AppError> {
+    let result = sqlx::query(r#"
+        UPDATE tasks
+        SET
+            project_id = project_id,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            project_id = $1 AND
+            state_id NOT IN (
+                SELECT id FROM workflow_states
+                WHERE workflow_id = task_workflow_id(tasks.user_id, $1)
+            )
+    "#)
+        .bind(project_id)
+        .execute(conn)
+        .await;
+    if let Err(e) = result {
+        error!("{:#?}", e);
+        return Err(AppError::InternalServer);
+    }
+    return Ok(());
+}
+
+async fn find(
+    id: i32,
+    conn: &mut PgConnection
+) -> Result<Workflow, AppError> {
+    let result = sqlx::query_as::<_, Workflow>(&format!(r#"
+        SELECT {WORKFLOW_COLUMNS}
+        FROM workflows
+        WHERE id = $1
+    "#))
+        .bind(id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(workflow) => return Ok(workflow),
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::NotFoundData),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
+
+async fn find_for_project(
+    project_id: i32,
+    conn: &mut PgConnection
+) -> Result<Workflow, AppError> {
+    let result = sqlx::query_as::<_, Workflow>(&format!(r#"
+        SELECT {WORKFLOW_COLUMNS}
+        FROM workflows
+        WHERE id = (
+            SELECT task_workflow_id(user_id, id) FROM projects
+            WHERE id = $1
+        )
+    "#))
+        .bind(project_id)
+        .fetch_one(conn)
+        .await;
+    match result {
+        Ok(workflow) => return Ok(workflow),
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::NotFoundData),
+        Err(e) => {
+            error!("{:#?}", e);
+            return Err(AppError::InternalServer);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,59 @@
+use std::{
+    future::Future,
+    io,
+    path::PathBuf
+};
+
+
+/// Where attachment blobs live. Blobs are addressed by an opaque `key`
+/// chosen by the caller; metadata stays in Postgres.
+pub trait Storage {
+    fn put(&self, key: &str, data: &[u8]) -> impl Future<Output = io::Result<()>> + Send;
+    fn get(&self, key: &str) -> impl Future<Output = io::Result<Vec<u8>>> + Send;
+    fn delete(&self, key: &str) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// Keeps blobs as plain files under `root`.
+pub struct LocalStorage {
+    root: PathBuf
+}
+
+impl LocalStorage {
+    fn path(&self, key: &str) -> PathBuf {
+        return self.root.join(key);
+    }
+}
+
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: &[u8]) -> io::Result<()> {
+        tokio::fs::create_dir_all(&self.root).await?;
+        return tokio::fs::write(self.path(key), data).await;
+    }
+
+    async fn get(&self, key: &str) -> io::Result<Vec<u8>> {
+        return tokio::fs::read(self.path(key)).await;
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match tokio::fs::remove_file(self.path(key)).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => return Ok(())
+        }
+    }
+}
+
+pub fn get_storage() -> LocalStorage {
+    let root = std::env::var("TODOLISTIFY_STORAGE_PATH")
+        .expect(">>> TODOLISTIFY_STORAGE_PATH NOT found!");
+    return LocalStorage {
+        root: PathBuf::from(root)
+    };
+}
+
+/// Total bytes of attachments a user can upload.
+pub fn get_quota() -> i64 {
+    return std::env::var("TODOLISTIFY_STORAGE_QUOTA")
+        .expect(">>> TODOLISTIFY_STORAGE_QUOTA NOT found!")
+        .parse::<i64>()
+        .expect(">>> TODOLISTIFY_STORAGE_QUOTA must be a number of bytes!");
+}
\ No newline at end of file
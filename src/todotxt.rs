@@ -0,0 +1,148 @@
+use regex::Regex;
+
+use crate::modules::transfer::TodoTxtRow;
+
+
+/// One task line of a todo.txt file (<https://github.com/todotxt/todo.txt>):
+///
+/// `x (A) 2025-07-22 2025-07-20 Call mom +Family @phone due:2025-07-25`
+///
+/// `+project`, `@context` and the `due:`, `t:` and `pri:` key-values are
+/// taken out of the title. A task has one project, the last `+project`, so
+/// earlier ones stay in the title, as do words like `+1` with no letter in
+/// them or too long for a name.
+pub struct Line {
+    pub done: bool,
+    pub priority: Option<char>,
+    pub title: String,
+    pub project: Option<String>,
+    pub contexts: Vec<String>,
+    pub due: Option<String>,
+    /// `t:`, the date the task becomes actionable.
+    pub threshold: Option<String>
+}
+
+pub fn parse(line: &str) -> Result<Line, String> {
+    let date = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+    let mut words = line.split_whitespace().peekable();
+    let done = words.next_if_eq(&"x").is_some();
+    let mut priority = words.next_if(|word| is_priority(word))
+        .and_then(|word| word.chars().nth(1));
+    // The completion date of a done task, then the creation date; we keep
+    // neither. An open task only has the creation date, so a second date
+    // already belongs to its title.
+    words.next_if(|word| date.is_match(word));
+    if done {
+        words.next_if(|word| date.is_match(word));
+    }
+
+    let mut title: Vec<&str> = Vec::new();
+    let mut project = None;
+    let mut contexts = Vec::new();
+    let mut due = None;
+    let mut threshold = None;
+    let words: Vec<&str> = words.collect();
+    let project_at = words.iter().rposition(|word| name(word, '+').is_some());
+    for (at, word) in words.into_iter().enumerate() {
+        if Some(at) == project_at {
+            project = name(word, '+').map(|name| name.to_string());
+        } else if let Some(context) = name(word, '@') {
+            contexts.push(context.to_string());
+        } else if let Some(value) = word.strip_prefix("due:") {
+            if !date.is_match(value) {
+                return Err("due: must be YYYY-MM-DD".to_string());
+            }
+            due = Some(value.to_string());
+        } else if let Some(value) = word.strip_prefix("t:") {
+            if !date.is_match(value) {
+                return Err("t: must be YYYY-MM-DD".to_string());
+            }
+            threshold = Some(value.to_string());
+        } else if let Some(value) = word.strip_prefix("pri:") {
+            // Completed tasks keep their priority as `pri:A`.
+            if !is_priority(&format!("({})", value)) {
+                return Err("pri: must be a letter from A to Z".to_string());
+            }
+            priority = priority.or(value.chars().next());
+        } else {
+            title.push(word);
+        }
+    }
+    return Ok(Line {
+        done,
+        priority,
+        title: title.join(" "),
+        project,
+        contexts,
+        due,
+        threshold
+    });
+}
+
+/// The name of a `+project` or `@context` word, which needs a letter and
+/// has to fit a tag name.
+fn name(word: &str, prefix: char) -> Option<&str> {
+    return word.strip_prefix(prefix).filter(|name| {
+        name.chars().any(char::is_alphabetic) && name.chars().count() <= 64
+    });
+}
+
+fn is_priority(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    return bytes.len() == 3 &&
+        bytes[0] == b'(' &&
+        bytes[1].is_ascii_uppercase() &&
+        bytes[2] == b')';
+}
+
+/// The todo.txt line of an exported task. Open tasks carry their priority
+/// as `(A)`, completed ones as `pri:A` after the `x` and the dates.
+pub fn format(row: &TodoTxtRow) -> String {
+    let mut words: Vec<String> = Vec::new();
+    if row.closed {
+        words.push("x".to_string());
+        words.push(row.completed_date.clone());
+    } else {
+        words.push(format!("({})", letter(&row.priority)));
+    }
+    words.push(row.created_date.clone());
+    words.push(row.title.split_whitespace().collect::<Vec<&str>>().join(" "));
+    if let Some(project) = &row.project {
+        words.push(format!("+{}", token(project)));
+    }
+    for tag in row.tags.iter() {
+        words.push(format!("@{}", token(tag)));
+    }
+    if let Some(due_date) = &row.due_date {
+        words.push(format!("due:{}", due_date));
+    }
+    if let Some(start_date) = &row.start_date {
+        words.push(format!("t:{}", start_date));
+    }
+    if row.closed {
+        words.push(format!("pri:{}", letter(&row.priority)));
+    }
+    return words.join(" ");
+}
+
+/// `A` is HIGH, `B` MEDIUM and any later letter LOW.
+pub fn priority(letter: char) -> &'static str {
+    match letter {
+        'A' => return "HIGH",
+        'B' => return "MEDIUM",
+        _ => return "LOW"
+    }
+}
+
+fn letter(priority: &str) -> char {
+    match priority {
+        "HIGH" => return 'A',
+        "LOW" => return 'C',
+        _ => return 'B'
+    }
+}
+
+/// A project or tag name as a single `+`/`@` word; whitespace becomes `-`.
+pub fn token(name: &str) -> String {
+    return name.split_whitespace().collect::<Vec<&str>>().join("-");
+}
\ No newline at end of file